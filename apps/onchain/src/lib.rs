@@ -1,8 +1,8 @@
 #![no_std]
 #![allow(unexpected_cfgs)]
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, symbol_short, token, Address, Env, Symbol,
-    Vec,
+    contract, contracterror, contractimpl, contracttype, symbol_short, token, xdr::ToXdr, Address,
+    Bytes, BytesN, Env, Map, String, Symbol, Vec,
 };
 
 impl VaultixEscrow {
@@ -35,6 +35,7 @@ pub enum MilestoneStatus {
     Pending,
     Released,
     Disputed,
+    Refunded,
 }
 
 #[contracttype]
@@ -42,7 +43,47 @@ pub enum MilestoneStatus {
 pub struct Milestone {
     pub amount: i128,
     pub status: MilestoneStatus,
-    pub description: Symbol,
+    /// Short human-readable title, 1 to `MAX_TITLE_LEN` bytes of UTF-8
+    pub title: String,
+    /// SHA-256 of the full off-chain specification, if one is published
+    pub content_hash: OptionalHash,
+    /// Token this milestone is paid in; `None` uses the escrow's `token_address`
+    pub token: OptionalAddress,
+    /// Unix timestamp after which the depositor may reclaim this milestone
+    pub due_date: Option<u64>,
+    /// Payee of this milestone; `None` pays the escrow's recipients
+    pub recipient: OptionalAddress,
+    /// SHA-256 of a secret whose reveal lets the payee claim this milestone
+    /// until its timelock (`due_date`, or the escrow deadline) passes
+    pub hashlock: OptionalHash,
+}
+
+/// Optional address for `contracttype` struct fields. soroban-sdk 20 has no
+/// `ScVal` conversion for `Option<Address>` fields, so structs use this
+/// instead and convert with `to_option`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OptionalAddress {
+    None,
+    Some(Address),
+}
+
+/// Optional 32-byte hash for `contracttype` struct fields, for the same
+/// reason as `OptionalAddress`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OptionalHash {
+    None,
+    Some(BytesN<32>),
+}
+
+impl OptionalAddress {
+    pub fn to_option(&self) -> Option<Address> {
+        match self {
+            OptionalAddress::None => None,
+            OptionalAddress::Some(address) => Some(address.clone()),
+        }
+    }
 }
 
 #[contracttype]
@@ -54,6 +95,7 @@ pub enum EscrowStatus {
     Cancelled, // Escrow cancelled, funds refunded
     Disputed,
     Resolved,
+    PartiallyFunded, // Some, but not all, milestones are covered by deposits
 }
 
 #[contracttype]
@@ -71,20 +113,188 @@ pub enum ContractState {
     Paused,
 }
 
+/// Linear release window of a streaming escrow. `paused_at` freezes vesting
+/// while a dispute is open.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StreamSchedule {
+    pub start_time: u64,
+    pub end_time: u64,
+    pub paused_at: Option<u64>,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReleaseMode {
+    /// Funds are released milestone by milestone
+    Milestones,
+    /// Funds vest linearly and are pulled with `claim_streamed`
+    Stream(StreamSchedule),
+}
+
+/// Who must authorize a milestone release.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ApprovalPolicy {
+    DepositorOnly,
+    ApproverOnly,
+    /// Either the depositor or the approver
+    Either,
+    /// The depositor and the approver together
+    Both,
+    /// `approval_threshold` of `approvers` via `approve_milestone`
+    Threshold,
+}
+
+/// Off-chain release authorization. The depositor signs the XDR of
+/// `(contract address, voucher)` with the ed25519 key registered through
+/// `register_voucher_key`.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ReleaseVoucher {
+    pub escrow_id: u64,
+    pub milestone_index: u32,
+    /// Any value not yet used by this depositor
+    pub nonce: u64,
+    /// Last ledger timestamp at which the voucher can be redeemed
+    pub expiry: u64,
+}
+
+/// Who bears the protocol fee on escrows created from a template.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FeePayer {
+    /// Fees come out of each payout, as for regular escrows
+    Recipient,
+    /// Milestones are grossed up so the recipient nets the template amounts
+    Depositor,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TemplateMilestone {
+    pub title: String,
+    /// Share of the escrow total; shares of a template sum to 10000
+    pub share_bps: u32,
+}
+
+/// Reusable milestone layout registered with `register_template`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowTemplate {
+    pub owner: Address,
+    pub token_address: Address,
+    pub milestones: Vec<TemplateMilestone>,
+    /// Escrow deadline, in seconds after creation
+    pub deadline_offset: u64,
+    pub fee_payer: FeePayer,
+}
+
+/// Result an oracle reports for a milestone.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AttestationOutcome {
+    Fulfilled,
+    Failed,
+}
+
+/// One payee of a multi-recipient escrow and its share of every payout.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PayoutSplit {
+    pub recipient: Address,
+    pub share_bps: u32,
+}
+
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct Escrow {
-    pub depositor: Address,
+    /// `None` while an invoice is waiting for its payer
+    pub depositor: OptionalAddress,
     pub recipient: Address,
     pub token_address: Address,
     pub total_amount: i128,
+    pub funded_amount: i128,
     pub total_released: i128,
+    pub released_by_token: Map<Address, i128>,
+    pub refunded_by_token: Map<Address, i128>,
     pub milestones: Vec<Milestone>,
     pub status: EscrowStatus,
     pub deadline: u64,
     pub resolution: Resolution,
+    pub release_mode: ReleaseMode,
+    /// Empty for single-recipient escrows. Otherwise payouts are split by
+    /// share and `recipient` is the first entry.
+    pub splits: Vec<PayoutSplit>,
+    /// Third party who may approve releases, depending on `approval_policy`
+    pub approver: OptionalAddress,
+    pub approval_policy: ApprovalPolicy,
+    /// Signers of a `Threshold` escrow; empty otherwise
+    pub approvers: Vec<Address>,
+    pub approval_threshold: u32,
+    /// Attests milestone outcomes through `attest_milestone`
+    pub oracle: OptionalAddress,
+    /// Only this address may pay an invoice; `None` lets anyone pay
+    pub payer: OptionalAddress,
+}
+
+impl Escrow {
+    /// Unpaid invoices have no depositor, so depositor actions fail on them.
+    fn depositor(&self) -> Result<Address, Error> {
+        self.depositor.to_option().ok_or(Error::InvalidEscrowStatus)
+    }
+}
+
+/// Bounds on the per-token total of a new escrow, set when the token is
+/// allowlisted.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TokenLimits {
+    pub min_amount: i128,
+    pub max_amount: i128,
+}
+
+/// Admin-configurable bounds enforced when escrows are created. Per-token
+/// minimum and maximum totals live in the token allowlist (`TokenLimits`).
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Limits {
+    pub max_milestones: u32,
+    /// Latest allowed deadline, in seconds from the current ledger time
+    pub max_deadline_horizon: u64,
+    /// Escrows a depositor may have open (not completed, cancelled or resolved)
+    pub max_active_escrows: u32,
+}
+
+/// Expected (ledger) versus actual (token-reported) contract holdings of a token.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TokenHoldings {
+    pub expected: i128,
+    pub actual: i128,
+}
+
+/// Parameters for one escrow in a `create_escrows_batch` call.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct EscrowSpec {
+    pub escrow_id: u64,
+    pub recipient: Address,
+    pub token_address: Address,
+    pub milestones: Vec<Milestone>,
+    pub deadline: u64,
+}
+
+/// Identifies the spec that made a batch invalid; `error` is the `Error` code.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BatchSpecError {
+    pub index: u32,
+    pub error: u32,
 }
 
+// The contract spec allows at most 50 error variants; new failure modes
+// should reuse an existing variant where the meaning fits.
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Error {
@@ -111,10 +321,124 @@ pub enum Error {
     AlreadyInDispute = 21,
     InvalidWinner = 22,
     ContractPaused = 23,
+    MilestoneNotFunded = 24,
+    MixedTokenPartialFunding = 25,
+    TokenNotAllowed = 26,
+    InvalidTokenLimits = 27,
+    EscrowAmountOutOfRange = 28,
+    NoSurplus = 29,
+    EscrowedFundsLocked = 30,
+    MilestoneRefunded = 31,
+    MilestoneNotOverdue = 32,
+    EmptyMilestoneTitle = 33,
+    MilestoneTitleTooLong = 34,
+    InvalidContentHash = 35,
+    InvalidLimits = 36,
+    TooManyActiveEscrows = 37,
+    NoMilestones = 38,
+    InvalidToken = 39,
+    SubscriptionNotFound = 40,
+    InvalidInterval = 41,
+    NothingToClaim = 42,
+    WrongReleaseMode = 43,
+    InvalidStreamWindow = 44,
+    InvalidSplits = 45,
+    InvalidApprover = 46,
+    InvalidThreshold = 47,
+    AlreadyApproved = 48,
+    ApprovalNotFound = 49,
+    /// A hashlock preimage or voucher is wrong, expired or already used
+    InvalidProof = 50,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SubscriptionStatus {
+    Active,
+    /// Stopped by the depositor; periods unlocked before the stop stay claimable
+    Stopped,
+    Completed,
+}
+
+/// Recurring escrow: `amount_per_period` unlocks every `interval` seconds
+/// after `start_time`, up to the number of periods the depositor has funded.
+/// Topping up a lapsed subscription moves `start_time` forward so the new
+/// periods unlock from the top-up onwards.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Subscription {
+    pub depositor: Address,
+    pub recipient: Address,
+    pub token_address: Address,
+    pub amount_per_period: i128,
+    pub interval: u64,
+    pub start_time: u64,
+    pub funded_periods: u32,
+    pub claimed_periods: u32,
+    pub status: SubscriptionStatus,
+}
+
+/// Settings that only some creation entrypoints configure.
+struct EscrowExtras {
+    release_mode: ReleaseMode,
+    splits: Vec<PayoutSplit>,
+    approver: OptionalAddress,
+    approval_policy: ApprovalPolicy,
+    approvers: Vec<Address>,
+    approval_threshold: u32,
+    oracle: OptionalAddress,
+    payer: OptionalAddress,
+}
+
+impl EscrowExtras {
+    fn new(env: &Env) -> Self {
+        EscrowExtras {
+            release_mode: ReleaseMode::Milestones,
+            splits: Vec::new(env),
+            approver: OptionalAddress::None,
+            approval_policy: ApprovalPolicy::DepositorOnly,
+            approvers: Vec::new(env),
+            approval_threshold: 0,
+            oracle: OptionalAddress::None,
+            payer: OptionalAddress::None,
+        }
+    }
+}
+
+/// Which party is invoking a release entrypoint.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Releaser {
+    Depositor,
+    Approver,
+    /// Threshold reached through `approve_milestone`
+    Approvals,
+    /// `Fulfilled` attestation from the escrow's oracle, already authorized
+    Oracle,
+    /// Hashlock preimage revealed by the milestone's payee, already verified
+    Preimage,
+    /// Depositor-signed voucher, already verified; counts as the depositor
+    Voucher,
+}
+
+/// How deposits move tokens into the contract.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum FundingSource {
+    /// `transfer_from` against an allowance granted to the contract
+    Allowance,
+    /// `transfer` authorized directly by the depositor
+    Direct,
 }
 
 const DEFAULT_FEE_BPS: i128 = 50;
 const BPS_DENOMINATOR: i128 = 10000;
+const MAX_BATCH_SIZE: u32 = 20;
+const MAX_RECIPIENTS: u32 = 10;
+const MAX_APPROVERS: u32 = 10;
+const DEFAULT_MAX_MILESTONES: u32 = 20;
+/// Upper bound for `Limits::max_milestones` so one escrow stays within
+/// Soroban's per-entry size budget.
+const MAX_MILESTONES_CAP: u32 = 100;
+const MAX_TITLE_LEN: u32 = 128;
 
 #[contract]
 pub struct VaultixEscrow;
@@ -262,6 +586,107 @@ impl VaultixEscrow {
         Ok(())
     }
 
+    /// Allowlists `token_address` for new escrows, or updates its limits if it
+    /// is already listed. Per-token escrow totals must fall within
+    /// `min_amount..=max_amount`.
+    pub fn add_token(
+        env: Env,
+        token_address: Address,
+        min_amount: i128,
+        max_amount: i128,
+    ) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        if min_amount < 0 || max_amount < min_amount {
+            return Err(Error::InvalidTokenLimits);
+        }
+
+        let limits = TokenLimits {
+            min_amount,
+            max_amount,
+        };
+        let mut tokens = get_allowed_tokens(&env);
+        tokens.set(token_address.clone(), limits);
+        env.storage().instance().set(&allowed_tokens_key(), &tokens);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "TokenAdded"),
+                token_address,
+            ),
+            (min_amount, max_amount),
+        );
+
+        Ok(())
+    }
+
+    /// Removes `token_address` from the allowlist. Existing escrows in that
+    /// token are unaffected; only new escrows are rejected.
+    pub fn remove_token(env: Env, token_address: Address) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let mut tokens = get_allowed_tokens(&env);
+        if !tokens.contains_key(token_address.clone()) {
+            return Err(Error::TokenNotAllowed);
+        }
+        tokens.remove(token_address.clone());
+        env.storage().instance().set(&allowed_tokens_key(), &tokens);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "TokenRemoved"),
+                token_address,
+            ),
+            (),
+        );
+
+        Ok(())
+    }
+
+    pub fn list_tokens(env: Env) -> Vec<Address> {
+        get_allowed_tokens(&env).keys()
+    }
+
+    pub fn get_token_limits(env: Env, token_address: Address) -> Result<TokenLimits, Error> {
+        get_allowed_tokens(&env)
+            .get(token_address)
+            .ok_or(Error::TokenNotAllowed)
+    }
+
+    pub fn get_limits(env: Env) -> Limits {
+        get_limits(&env)
+    }
+
+    pub fn set_limits(env: Env, limits: Limits) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        if limits.max_milestones == 0
+            || limits.max_milestones > MAX_MILESTONES_CAP
+            || limits.max_deadline_horizon == 0
+            || limits.max_active_escrows == 0
+        {
+            return Err(Error::InvalidLimits);
+        }
+
+        let old_limits = get_limits(&env);
+        env.storage().instance().set(&limits_key(), &limits);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "LimitsUpdated"),
+            ),
+            (old_limits, limits),
+        );
+
+        Ok(())
+    }
+
     pub fn create_escrow(
         env: Env,
         escrow_id: u64,
@@ -274,55 +699,165 @@ impl VaultixEscrow {
         depositor.require_auth();
         ensure_not_paused(&env)?;
 
-        if depositor == recipient {
-            return Err(Error::SelfDealing);
-        }
+        let spec = EscrowSpec {
+            escrow_id,
+            recipient,
+            token_address,
+            milestones,
+            deadline,
+        };
+        let total_amount = validate_escrow_spec(&env, &depositor, &spec, 0)?;
+        store_new_escrow(
+            &env,
+            &depositor,
+            &spec,
+            total_amount,
+            EscrowStatus::Created,
+            EscrowExtras::new(&env),
+        );
 
-        let storage_key = get_storage_key(escrow_id);
-        if env.storage().persistent().has(&storage_key) {
-            return Err(Error::EscrowAlreadyExists);
-        }
+        Ok(())
+    }
 
-        let total_amount = validate_milestones(&milestones)?;
+    /// Creates an escrow and pulls its full amount in the same call. The
+    /// tokens move with a plain `transfer` authorized by the depositor, so no
+    /// prior `approve` is needed and wallets sign a single transaction.
+    pub fn create_and_fund_escrow(
+        env: Env,
+        escrow_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        milestones: Vec<Milestone>,
+        deadline: u64,
+    ) -> Result<(), Error> {
+        depositor.require_auth();
+        ensure_not_paused(&env)?;
 
-        let mut initialized_milestones = Vec::new(&env);
-        for milestone in milestones.iter() {
-            let mut m = milestone.clone();
-            m.status = MilestoneStatus::Pending;
-            initialized_milestones.push_back(m);
+        let spec = EscrowSpec {
+            escrow_id,
+            recipient,
+            token_address,
+            milestones,
+            deadline,
+        };
+        let total_amount = validate_escrow_spec(&env, &depositor, &spec, 0)?;
+
+        let totals = token_totals(&env, &spec.token_address, &spec.milestones)?;
+        for (token_address, amount) in totals.iter() {
+            pull_funds(
+                &env,
+                &token_address,
+                &depositor,
+                amount,
+                FundingSource::Direct,
+            )?;
         }
 
-        let escrow = Escrow {
-            depositor: depositor.clone(),
-            recipient: recipient.clone(),
-            token_address: token_address.clone(),
+        store_new_escrow(
+            &env,
+            &depositor,
+            &spec,
             total_amount,
-            total_released: 0,
-            milestones: initialized_milestones,
-            status: EscrowStatus::Created,
-            deadline,
-            resolution: Resolution::None,
+            EscrowStatus::Active,
+            EscrowExtras::new(&env),
+        );
+
+        Ok(())
+    }
+
+    /// Creates every escrow in `specs` for `depositor` under a single auth.
+    /// When `fund` is set, the combined total per token is pulled with one
+    /// `transfer_from` each and all escrows start out `Active`. Either every
+    /// spec is accepted or nothing is written: a rejected spec is returned
+    /// as `Some(BatchSpecError)` with its index and error code.
+    pub fn create_escrows_batch(
+        env: Env,
+        depositor: Address,
+        specs: Vec<EscrowSpec>,
+        fund: bool,
+    ) -> Result<Option<BatchSpecError>, Error> {
+        depositor.require_auth();
+        ensure_not_paused(&env)?;
+
+        let totals = match validate_batch(&env, &depositor, &specs)? {
+            Ok(totals) => totals,
+            Err((index, error)) => {
+                return Ok(Some(BatchSpecError {
+                    index,
+                    error: error as u32,
+                }))
+            }
         };
 
-        env.storage().persistent().set(&storage_key, &escrow);
-        env.storage()
-            .persistent()
-            .extend_ttl(&storage_key, 100, 2_000_000);
+        if fund {
+            let mut deposits: Map<Address, i128> = Map::new(&env);
+            for spec in specs.iter() {
+                let spec_totals = token_totals(&env, &spec.token_address, &spec.milestones)?;
+                for (token_address, amount) in spec_totals.iter() {
+                    add_token_amount(&mut deposits, token_address, amount)?;
+                }
+            }
+            for (token_address, amount) in deposits.iter() {
+                pull_funds(
+                    &env,
+                    &token_address,
+                    &depositor,
+                    amount,
+                    FundingSource::Allowance,
+                )?;
+            }
+        }
 
-        // Standardized Event
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "EscrowCreated"),
-                escrow_id,
-            ),
-            (depositor, recipient, token_address, total_amount, deadline),
-        );
+        let status = if fund {
+            EscrowStatus::Active
+        } else {
+            EscrowStatus::Created
+        };
+        for (spec, total) in specs.iter().zip(totals.iter()) {
+            store_new_escrow(
+                &env,
+                &depositor,
+                &spec,
+                total,
+                status,
+                EscrowExtras::new(&env),
+            );
+        }
 
-        Ok(())
+        Ok(None)
+    }
+
+    /// Dry-run of `create_escrows_batch`: returns the first spec that would be
+    /// rejected together with its error code, or `None` if the batch is valid.
+    pub fn validate_escrows_batch(
+        env: Env,
+        depositor: Address,
+        specs: Vec<EscrowSpec>,
+    ) -> Result<Option<BatchSpecError>, Error> {
+        let failure = validate_batch(&env, &depositor, &specs)?.err();
+        Ok(failure.map(|(index, error)| BatchSpecError {
+            index,
+            error: error as u32,
+        }))
     }
 
+    /// Funds a `Created` escrow through the contract's token allowance, which
+    /// the depositor must have granted with `approve` beforehand.
     pub fn deposit_funds(env: Env, escrow_id: u64) -> Result<(), Error> {
+        fund_escrow(&env, escrow_id, FundingSource::Allowance)
+    }
+
+    /// Funds a `Created` escrow with a direct `transfer` authorized by the
+    /// depositor, so no separate `approve` is required.
+    pub fn deposit_funds_direct(env: Env, escrow_id: u64) -> Result<(), Error> {
+        fund_escrow(&env, escrow_id, FundingSource::Direct)
+    }
+
+    /// Deposits part of an escrow's total with a direct `transfer` authorized
+    /// by the depositor. Milestones become releasable in order as the funded
+    /// amount covers them; the escrow turns `Active` once fully funded.
+    pub fn deposit_partial(env: Env, escrow_id: u64, amount: i128) -> Result<(), Error> {
         let storage_key = get_storage_key(escrow_id);
         ensure_not_paused(&env)?;
 
@@ -331,35 +866,67 @@ impl VaultixEscrow {
             .persistent()
             .get(&storage_key)
             .ok_or(Error::EscrowNotFound)?;
-        escrow.depositor.require_auth();
+        escrow.depositor()?.require_auth();
 
-        if escrow.status != EscrowStatus::Created {
+        if escrow.status != EscrowStatus::Created && escrow.status != EscrowStatus::PartiallyFunded
+        {
             return Err(Error::EscrowAlreadyFunded);
         }
+        if amount <= 0 {
+            return Err(Error::ZeroAmount);
+        }
+        // Incremental funding is tracked as a single amount of the escrow
+        // token, which only has a meaning when every milestone is paid in it
+        let totals = token_totals(&env, &escrow.token_address, &escrow.milestones)?;
+        if totals.len() != 1 || !totals.contains_key(escrow.token_address.clone()) {
+            return Err(Error::MixedTokenPartialFunding);
+        }
+        let funded_amount = escrow
+            .funded_amount
+            .checked_add(amount)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        if funded_amount > escrow.total_amount {
+            return Err(Error::TotalAmountMismatch);
+        }
 
-        let token_client = token::Client::new(&env, &escrow.token_address);
-        token_client.transfer_from(
-            &env.current_contract_address(),
-            &escrow.depositor,
-            &env.current_contract_address(),
-            &escrow.total_amount,
-        );
-
-        escrow.status = EscrowStatus::Active;
+        pull_funds(
+            &env,
+            &escrow.token_address,
+            &escrow.depositor()?,
+            amount,
+            FundingSource::Direct,
+        )?;
+
+        escrow.funded_amount = funded_amount;
+        escrow.status = if funded_amount == escrow.total_amount {
+            EscrowStatus::Active
+        } else {
+            EscrowStatus::PartiallyFunded
+        };
         env.storage().persistent().set(&storage_key, &escrow);
         env.storage()
             .persistent()
             .extend_ttl(&storage_key, 100, 2_000_000);
 
-        // Standardized Event
         env.events().publish(
             (
                 Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "EscrowFunded"),
+                Symbol::new(&env, "FundsDeposited"),
                 escrow_id,
             ),
-            escrow.total_amount,
+            (amount, funded_amount),
         );
+        if escrow.status == EscrowStatus::Active {
+            // Standardized Event
+            env.events().publish(
+                (
+                    Symbol::new(&env, "Vaultix"),
+                    Symbol::new(&env, "EscrowFunded"),
+                    escrow_id,
+                ),
+                escrow.total_amount,
+            );
+        }
 
         Ok(())
     }
@@ -372,78 +939,111 @@ impl VaultixEscrow {
             .ok_or(Error::EscrowNotFound)
     }
 
-    pub fn get_state(env: Env, escrow_id: u64) -> Result<EscrowStatus, Error> {
-        let escrow = Self::get_escrow(env, escrow_id)?;
-        Ok(escrow.status)
+    /// Amount owed per token across all milestones, i.e. what a full deposit
+    /// pulls from the depositor.
+    pub fn get_token_totals(env: Env, escrow_id: u64) -> Result<Map<Address, i128>, Error> {
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        token_totals(&env, &escrow.token_address, &escrow.milestones)
     }
 
-    pub fn release_milestone(env: Env, escrow_id: u64, milestone_index: u32) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
-        ensure_not_paused(&env)?;
+    /// Compares the contract's bookkeeping for `token_address` with the balance
+    /// the token contract actually reports.
+    pub fn get_token_holdings(env: Env, token_address: Address) -> TokenHoldings {
+        let token_client = token::Client::new(&env, &token_address);
+        TokenHoldings {
+            expected: get_expected_holdings(&env, &token_address),
+            actual: token_client.balance(&env.current_contract_address()),
+        }
+    }
 
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
-        escrow.depositor.require_auth();
+    /// Total the contract owes across all live escrows in `token_address`.
+    pub fn get_liabilities(env: Env, token_address: Address) -> i128 {
+        get_expected_holdings(&env, &token_address)
+    }
 
-        if escrow.status != EscrowStatus::Active {
-            return Err(Error::EscrowNotActive);
-        }
-        if milestone_index >= escrow.milestones.len() {
-            return Err(Error::MilestoneNotFound);
-        }
+    /// Whether the contract's balance of `token_address` covers its liabilities.
+    pub fn check_solvency(env: Env, token_address: Address) -> bool {
+        let holdings = Self::get_token_holdings(env, token_address);
+        holdings.actual >= holdings.expected
+    }
 
-        let mut milestone = escrow
-            .milestones
-            .get(milestone_index)
-            .ok_or(Error::MilestoneNotFound)?;
-        if milestone.status == MilestoneStatus::Released {
-            return Err(Error::MilestoneAlreadyReleased);
+    /// Moves whatever the contract holds above its liabilities in
+    /// `token_address` to `to`. Escrowed funds can never be swept.
+    pub fn sweep_surplus(env: Env, token_address: Address, to: Address) -> Result<i128, Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let surplus = get_surplus(&env, &token_address)?;
+        if surplus <= 0 {
+            return Err(Error::NoSurplus);
         }
 
-        let (treasury, fee_bps) = Self::get_config(env.clone())?;
-        let fee = calculate_fee(milestone.amount, fee_bps)?;
-        let payout = milestone
-            .amount
-            .checked_sub(fee)
-            .ok_or(Error::InvalidMilestoneAmount)?;
+        send_tokens(&env, &token_address, &to, surplus)?;
 
-        let token_client = token::Client::new(&env, &escrow.token_address);
-        token_client.transfer(&env.current_contract_address(), &escrow.recipient, &payout);
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "SurplusSwept"),
+                token_address,
+            ),
+            (to, surplus),
+        );
 
-        if fee > 0 {
-            token_client.transfer(&env.current_contract_address(), &treasury, &fee);
-        }
+        Ok(surplus)
+    }
 
-        milestone.status = MilestoneStatus::Released;
-        escrow.milestones.set(milestone_index, milestone.clone());
+    /// Returns `amount` of `token_address` that was sent to the contract by
+    /// mistake. Only the balance above liabilities can be moved, so funds
+    /// backing live escrows stay untouched.
+    pub fn rescue_tokens(
+        env: Env,
+        token_address: Address,
+        to: Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
 
-        escrow.total_released = escrow
-            .total_released
-            .checked_add(milestone.amount)
-            .ok_or(Error::InvalidMilestoneAmount)?;
+        if amount <= 0 {
+            return Err(Error::ZeroAmount);
+        }
+        if amount > get_surplus(&env, &token_address)? {
+            return Err(Error::EscrowedFundsLocked);
+        }
 
-        env.storage().persistent().set(&storage_key, &escrow);
-        env.storage()
-            .persistent()
-            .extend_ttl(&storage_key, 100, 2_000_000);
+        send_tokens(&env, &token_address, &to, amount)?;
 
-        // Standardized Event
         env.events().publish(
             (
                 Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "MilestoneReleased"),
-                escrow_id,
-                milestone_index,
+                Symbol::new(&env, "TokensRescued"),
+                token_address,
             ),
-            (payout, fee),
+            (to, amount),
         );
 
         Ok(())
     }
 
+    pub fn get_state(env: Env, escrow_id: u64) -> Result<EscrowStatus, Error> {
+        let escrow = Self::get_escrow(env, escrow_id)?;
+        Ok(escrow.status)
+    }
+
+    pub fn release_milestone(env: Env, escrow_id: u64, milestone_index: u32) -> Result<(), Error> {
+        release_milestone(&env, escrow_id, milestone_index, Releaser::Depositor)
+    }
+
+    /// Release entrypoint for the escrow's approver, allowed under the
+    /// `ApproverOnly`, `Either` and `Both` policies.
+    pub fn release_milestone_by_approver(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<(), Error> {
+        release_milestone(&env, escrow_id, milestone_index, Releaser::Approver)
+    }
+
     pub fn confirm_delivery(
         env: Env,
         escrow_id: u64,
@@ -460,10 +1060,31 @@ impl VaultixEscrow {
             .ok_or(Error::EscrowNotFound)?;
         buyer.require_auth();
 
-        if escrow.depositor != buyer {
+        if !is_depositor(&escrow, &buyer) {
             return Err(Error::UnauthorizedAccess);
         }
-        if escrow.status != EscrowStatus::Active {
+        // The buyer has signed as depositor; the policy may still forbid that
+        // or require the approver as well
+        match escrow.approval_policy {
+            ApprovalPolicy::DepositorOnly | ApprovalPolicy::Either => {}
+            ApprovalPolicy::ApproverOnly | ApprovalPolicy::Threshold => {
+                return Err(Error::UnauthorizedAccess)
+            }
+            ApprovalPolicy::Both => escrow
+                .approver
+                .to_option()
+                .ok_or(Error::UnauthorizedAccess)?
+                .require_auth(),
+        }
+        if escrow.release_mode != ReleaseMode::Milestones {
+            return Err(Error::WrongReleaseMode);
+        }
+
+        // Completed escrows fall through so re-releases report MilestoneAlreadyReleased
+        if !matches!(
+            escrow.status,
+            EscrowStatus::Active | EscrowStatus::PartiallyFunded | EscrowStatus::Completed
+        ) {
             return Err(Error::EscrowNotActive);
         }
         if milestone_index >= escrow.milestones.len() {
@@ -477,6 +1098,10 @@ impl VaultixEscrow {
         if milestone.status == MilestoneStatus::Released {
             return Err(Error::MilestoneAlreadyReleased);
         }
+        if milestone.status == MilestoneStatus::Refunded {
+            return Err(Error::MilestoneRefunded);
+        }
+        ensure_milestone_funded(&escrow, milestone_index)?;
 
         milestone.status = MilestoneStatus::Released;
         escrow.milestones.set(milestone_index, milestone.clone());
@@ -485,13 +1110,16 @@ impl VaultixEscrow {
             .total_released
             .checked_add(milestone.amount)
             .ok_or(Error::InvalidMilestoneAmount)?;
+        let token_address = milestone_token(&escrow, &milestone);
+        add_token_amount(
+            &mut escrow.released_by_token,
+            token_address.clone(),
+            milestone.amount,
+        )?;
 
-        let token_client = token::Client::new(&env, &escrow.token_address);
-        token_client.transfer(
-            &env.current_contract_address(),
-            &escrow.recipient,
-            &milestone.amount,
-        );
+        let completed = mark_completed_if_all_settled(&mut escrow);
+
+        pay_milestone(&env, &escrow, &milestone, &token_address, milestone.amount)?;
 
         env.storage().persistent().set(&storage_key, &escrow);
 
@@ -505,11 +1133,21 @@ impl VaultixEscrow {
             ),
             (milestone.amount, 0i128),
         );
+        if completed {
+            finish_completed_escrow(&env, escrow_id, &escrow.depositor()?);
+        }
 
         Ok(())
     }
 
-    pub fn raise_dispute(env: Env, escrow_id: u64, caller: Address) -> Result<(), Error> {
+    /// Returns an overdue milestone's funds to the depositor. The milestone is
+    /// marked `Refunded` and no platform fee is charged, since nothing was
+    /// delivered.
+    pub fn reclaim_overdue_milestone(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<(), Error> {
         let storage_key = get_storage_key(escrow_id);
         ensure_not_paused(&env)?;
 
@@ -518,46 +1156,278 @@ impl VaultixEscrow {
             .persistent()
             .get(&storage_key)
             .ok_or(Error::EscrowNotFound)?;
+        escrow.depositor()?.require_auth();
 
-        if caller != escrow.depositor && caller != escrow.recipient {
-            return Err(Error::UnauthorizedAccess);
+        if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::PartiallyFunded {
+            return Err(Error::EscrowNotActive);
         }
-        caller.require_auth();
 
-        if escrow.status == EscrowStatus::Disputed {
-            return Err(Error::AlreadyInDispute);
+        let mut milestone = escrow
+            .milestones
+            .get(milestone_index)
+            .ok_or(Error::MilestoneNotFound)?;
+        if milestone.status == MilestoneStatus::Released {
+            return Err(Error::MilestoneAlreadyReleased);
         }
-        if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
-            return Err(Error::InvalidEscrowStatus);
+        if milestone.status == MilestoneStatus::Refunded {
+            return Err(Error::MilestoneRefunded);
+        }
+        if !is_overdue(&env, &escrow, &milestone) {
+            return Err(Error::MilestoneNotOverdue);
+        }
+        ensure_milestone_funded(&escrow, milestone_index)?;
+
+        let token_address = milestone_token(&escrow, &milestone);
+        push_funds(&env, &token_address, &escrow.depositor()?, milestone.amount)?;
+
+        milestone.status = MilestoneStatus::Refunded;
+        escrow.milestones.set(milestone_index, milestone.clone());
+        add_token_amount(
+            &mut escrow.refunded_by_token,
+            token_address,
+            milestone.amount,
+        )?;
+        let completed = mark_completed_if_all_settled(&mut escrow);
+
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "MilestoneReclaimed"),
+                escrow_id,
+                milestone_index,
+            ),
+            milestone.amount,
+        );
+        if completed {
+            finish_completed_escrow(&env, escrow_id, &escrow.depositor()?);
         }
 
-        let mut updated_milestones = Vec::new(&env);
-        for milestone in escrow.milestones.iter() {
-            let mut m = milestone.clone();
-            if m.status == MilestoneStatus::Pending {
-                m.status = MilestoneStatus::Disputed;
+        Ok(())
+    }
+
+    /// Indices of pending milestones whose due date, or hashlock timelock,
+    /// has passed.
+    pub fn get_overdue_milestones(env: Env, escrow_id: u64) -> Result<Vec<u32>, Error> {
+        let escrow = Self::get_escrow(env.clone(), escrow_id)?;
+        let mut overdue = Vec::new(&env);
+        for (index, milestone) in escrow.milestones.iter().enumerate() {
+            if is_overdue(&env, &escrow, &milestone) {
+                overdue.push_back(index as u32);
             }
-            updated_milestones.push_back(m);
         }
+        Ok(overdue)
+    }
 
-        escrow.milestones = updated_milestones;
-        escrow.status = EscrowStatus::Disputed;
-        escrow.resolution = Resolution::None;
-        env.storage().persistent().set(&storage_key, &escrow);
+    /// HTLC claim: the milestone's payee reveals the preimage of its
+    /// `hashlock` before the timelock to release it. The preimage is
+    /// published in the event so the counterparty can use it elsewhere.
+    pub fn claim_with_preimage(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        preimage: Bytes,
+    ) -> Result<(), Error> {
+        ensure_not_paused(&env)?;
+
+        let escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&get_storage_key(escrow_id))
+            .ok_or(Error::EscrowNotFound)?;
+        let milestone = escrow
+            .milestones
+            .get(milestone_index)
+            .ok_or(Error::MilestoneNotFound)?;
+        milestone
+            .recipient
+            .to_option()
+            .unwrap_or_else(|| escrow.recipient.clone())
+            .require_auth();
+
+        let OptionalHash::Some(hashlock) = &milestone.hashlock else {
+            return Err(Error::WrongReleaseMode);
+        };
+        if let Some(timelock) = milestone_timelock(&escrow, &milestone) {
+            if env.ledger().timestamp() > timelock {
+                return Err(Error::InvalidDeadline);
+            }
+        }
+        if env.crypto().sha256(&preimage) != *hashlock {
+            return Err(Error::InvalidProof);
+        }
+
+        release_milestone(&env, escrow_id, milestone_index, Releaser::Preimage)?;
 
-        // Standardized Event
         env.events().publish(
             (
                 Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "DisputeRaised"),
+                Symbol::new(&env, "HashlockClaimed"),
                 escrow_id,
+                milestone_index,
+            ),
+            preimage,
+        );
+
+        Ok(())
+    }
+
+    /// Registers (or rotates) the ed25519 public key whose signatures on
+    /// `ReleaseVoucher`s count as the depositor's release approval.
+    pub fn register_voucher_key(
+        env: Env,
+        depositor: Address,
+        public_key: BytesN<32>,
+    ) -> Result<(), Error> {
+        depositor.require_auth();
+
+        let key = voucher_key_key(&depositor);
+        env.storage().persistent().set(&key, &public_key);
+        env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "VoucherKeyRegistered"),
+            ),
+            (depositor, public_key),
+        );
+
+        Ok(())
+    }
+
+    /// Releases a milestone with a voucher signed off-chain by the depositor.
+    /// Submitted by the milestone's payee; an invalid signature traps.
+    pub fn redeem_voucher(
+        env: Env,
+        voucher: ReleaseVoucher,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        ensure_not_paused(&env)?;
+
+        let escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&get_storage_key(voucher.escrow_id))
+            .ok_or(Error::EscrowNotFound)?;
+        let milestone = escrow
+            .milestones
+            .get(voucher.milestone_index)
+            .ok_or(Error::MilestoneNotFound)?;
+        milestone
+            .recipient
+            .to_option()
+            .unwrap_or_else(|| escrow.recipient.clone())
+            .require_auth();
+
+        let public_key: BytesN<32> = env
+            .storage()
+            .persistent()
+            .get(&voucher_key_key(&escrow.depositor()?))
+            .ok_or(Error::UnauthorizedAccess)?;
+        if env.ledger().timestamp() > voucher.expiry {
+            return Err(Error::InvalidProof);
+        }
+        let nonce_key = voucher_nonce_key(&escrow.depositor()?, voucher.nonce);
+        if env.storage().persistent().has(&nonce_key) {
+            return Err(Error::InvalidProof);
+        }
+
+        let message = (env.current_contract_address(), voucher).to_xdr(&env);
+        env.crypto()
+            .ed25519_verify(&public_key, &message, &signature);
+
+        env.storage().persistent().set(&nonce_key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&nonce_key, 100, 2_000_000);
+
+        release_milestone(
+            &env,
+            voucher.escrow_id,
+            voucher.milestone_index,
+            Releaser::Voucher,
+        )?;
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "VoucherRedeemed"),
+                voucher.escrow_id,
+                voucher.milestone_index,
             ),
-            caller,
+            voucher.nonce,
         );
 
         Ok(())
     }
 
+    pub fn raise_dispute(env: Env, escrow_id: u64, caller: Address) -> Result<(), Error> {
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+
+        if !is_depositor(&escrow, &caller) && !is_recipient(&escrow, &caller) {
+            return Err(Error::UnauthorizedAccess);
+        }
+        caller.require_auth();
+        // A depositor dispute would freeze a live hashlock claim
+        if is_depositor(&escrow, &caller) && has_live_hashlock(&env, &escrow) {
+            return Err(Error::MilestoneNotOverdue);
+        }
+
+        open_dispute(&env, escrow_id, &mut escrow, caller)
+    }
+
+    /// Entry point for the escrow's oracle: a `Fulfilled` outcome releases
+    /// the milestone (fees apply), `Failed` puts the escrow into dispute.
+    pub fn attest_milestone(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        outcome: AttestationOutcome,
+    ) -> Result<(), Error> {
+        ensure_not_paused(&env)?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&get_storage_key(escrow_id))
+            .ok_or(Error::EscrowNotFound)?;
+        let oracle = escrow.oracle.to_option().ok_or(Error::UnauthorizedAccess)?;
+        oracle.require_auth();
+
+        if milestone_index >= escrow.milestones.len() {
+            return Err(Error::MilestoneNotFound);
+        }
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "MilestoneAttested"),
+                escrow_id,
+                milestone_index,
+            ),
+            outcome,
+        );
+
+        match outcome {
+            AttestationOutcome::Fulfilled => {
+                release_milestone(&env, escrow_id, milestone_index, Releaser::Oracle)
+            }
+            AttestationOutcome::Failed => open_dispute(&env, escrow_id, &mut escrow, oracle),
+        }
+    }
+
     pub fn resolve_dispute(env: Env, escrow_id: u64, winner: Address) -> Result<(), Error> {
         let admin = get_admin(&env)?;
         admin.require_auth();
@@ -572,35 +1442,47 @@ impl VaultixEscrow {
         if escrow.status != EscrowStatus::Disputed {
             return Err(Error::InvalidEscrowStatus);
         }
-        if winner != escrow.depositor && winner != escrow.recipient {
+        if !is_depositor(&escrow, &winner) && !is_recipient(&escrow, &winner) {
             return Err(Error::InvalidWinner);
         }
 
-        let outstanding = escrow
-            .total_amount
-            .checked_sub(escrow.total_released)
-            .ok_or(Error::InvalidMilestoneAmount)?;
-        let token_client = token::Client::new(&env, &escrow.token_address);
+        let outstanding = held_by_token(&env, &escrow)?;
 
-        if winner == escrow.recipient {
+        if !is_depositor(&escrow, &winner) {
+            // Held funds cover open milestones in order; each share goes to
+            // that milestone's payee and anything left to the escrow recipients
+            let mut remaining = outstanding.clone();
             let mut updated_milestones = Vec::new(&env);
             for milestone in escrow.milestones.iter() {
                 let mut m = milestone.clone();
-                if m.status != MilestoneStatus::Released {
+                if m.status != MilestoneStatus::Released && m.status != MilestoneStatus::Refunded {
+                    let token_address = milestone_token(&escrow, &m);
+                    let available = remaining.get(token_address.clone()).unwrap_or(0);
+                    let amount = m.amount.min(available);
+                    if amount > 0 {
+                        pay_milestone(&env, &escrow, &m, &token_address, amount)?;
+                        remaining.set(token_address, available - amount);
+                    }
                     m.status = MilestoneStatus::Released;
                 }
                 updated_milestones.push_back(m);
             }
+            for (token_address, amount) in remaining.iter() {
+                if amount > 0 {
+                    pay_recipients(&env, &escrow, &token_address, amount)?;
+                }
+            }
             escrow.milestones = updated_milestones;
-            escrow.total_released = escrow.total_amount;
             escrow.resolution = Resolution::Recipient;
 
-            if outstanding > 0 {
-                token_client.transfer(
-                    &env.current_contract_address(),
-                    &escrow.recipient,
-                    &outstanding,
-                );
+            for (token_address, amount) in outstanding.iter() {
+                if amount > 0 {
+                    escrow.total_released = escrow
+                        .total_released
+                        .checked_add(amount)
+                        .ok_or(Error::InvalidMilestoneAmount)?;
+                    add_token_amount(&mut escrow.released_by_token, token_address, amount)?;
+                }
             }
         } else {
             let mut updated_milestones = Vec::new(&env);
@@ -614,17 +1496,16 @@ impl VaultixEscrow {
             escrow.milestones = updated_milestones;
             escrow.resolution = Resolution::Depositor;
 
-            if outstanding > 0 {
-                token_client.transfer(
-                    &env.current_contract_address(),
-                    &escrow.depositor,
-                    &outstanding,
-                );
+            for (token_address, amount) in outstanding.iter() {
+                if amount > 0 {
+                    push_funds(&env, &token_address, &escrow.depositor()?, amount)?;
+                }
             }
         }
 
         escrow.status = EscrowStatus::Resolved;
         env.storage().persistent().set(&storage_key, &escrow);
+        release_active_slot(&env, &escrow.depositor()?);
 
         // Standardized Event
         env.events().publish(
@@ -648,42 +1529,52 @@ impl VaultixEscrow {
             .persistent()
             .get(&storage_key)
             .ok_or(Error::EscrowNotFound)?;
-        escrow.depositor.require_auth();
+        escrow.depositor()?.require_auth();
 
-        if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
+        if !matches!(
+            escrow.status,
+            EscrowStatus::Active | EscrowStatus::PartiallyFunded | EscrowStatus::Created
+        ) {
             return Err(Error::InvalidEscrowStatus);
         }
-        if escrow.total_released > 0 {
+        // Funds behind a live hashlock stay claimable until its timelock passes
+        if escrow.status != EscrowStatus::Created && has_live_hashlock(&env, &escrow) {
+            return Err(Error::MilestoneNotOverdue);
+        }
+        // Streams settle what has vested with the recipient and refund the rest
+        let config = Self::get_config(env.clone());
+        if let ReleaseMode::Stream(schedule) = escrow.release_mode {
+            let vested = streamed_amount(&env, &escrow, &schedule)?;
+            pay_streamed(&env, &mut escrow, vested, config.clone().ok())?;
+        } else if escrow.total_released > 0 {
             return Err(Error::MilestoneAlreadyReleased);
         }
 
-        if escrow.status == EscrowStatus::Active {
-            let token_client = token::Client::new(&env, &escrow.token_address);
-
-            let refund_amount = if let Ok((treasury, fee_bps)) = Self::get_config(env.clone()) {
-                let fee = calculate_fee(escrow.total_amount, fee_bps)?;
+        // Only what is still held is refunded, with the fee taken per token
+        for (token_address, funded) in held_by_token(&env, &escrow)?.iter() {
+            if funded <= 0 {
+                continue;
+            }
+            let refund_amount = if let Ok((treasury, fee_bps)) = config.clone() {
+                let fee = calculate_fee(funded, fee_bps)?;
                 if fee > 0 {
-                    token_client.transfer(&env.current_contract_address(), &treasury, &fee);
+                    push_funds(&env, &token_address, &treasury, fee)?;
                 }
-                escrow
-                    .total_amount
+                funded
                     .checked_sub(fee)
                     .ok_or(Error::InvalidMilestoneAmount)?
             } else {
-                escrow.total_amount
+                funded
             };
 
             if refund_amount > 0 {
-                token_client.transfer(
-                    &env.current_contract_address(),
-                    &escrow.depositor,
-                    &refund_amount,
-                );
+                push_funds(&env, &token_address, &escrow.depositor()?, refund_amount)?;
             }
         }
 
         escrow.status = EscrowStatus::Cancelled;
         env.storage().persistent().set(&storage_key, &escrow);
+        release_active_slot(&env, &escrow.depositor()?);
         env.storage()
             .persistent()
             .extend_ttl(&storage_key, 100, 2_000_000);
@@ -695,12 +1586,16 @@ impl VaultixEscrow {
                 Symbol::new(&env, "EscrowCancelled"),
                 escrow_id,
             ),
-            escrow.depositor.clone(),
+            escrow.depositor()?,
         );
 
         Ok(())
     }
 
+    /// Kept for compatibility: releases now complete the escrow automatically,
+    /// so this is a no-op for escrows that are already `Completed`. Escrows
+    /// whose last milestone was released before auto-completion existed are
+    /// still transitioned here.
     pub fn complete_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
         let storage_key = get_storage_key(escrow_id);
         ensure_not_paused(&env)?;
@@ -710,40 +1605,994 @@ impl VaultixEscrow {
             .persistent()
             .get(&storage_key)
             .ok_or(Error::EscrowNotFound)?;
-        escrow.depositor.require_auth();
 
+        if escrow.status == EscrowStatus::Completed {
+            return Ok(());
+        }
         if escrow.status != EscrowStatus::Active {
             return Err(Error::InvalidEscrowStatus);
         }
-        if !verify_all_released(&escrow.milestones) {
+        if !mark_completed_if_all_settled(&mut escrow) {
             return Err(Error::EscrowNotActive);
         }
 
-        escrow.status = EscrowStatus::Completed;
         env.storage().persistent().set(&storage_key, &escrow);
         env.storage()
             .persistent()
             .extend_ttl(&storage_key, 100, 2_000_000);
 
-        // Standardized Event
+        finish_completed_escrow(&env, escrow_id, &escrow.depositor()?);
+
+        Ok(())
+    }
+
+    /// Creates an escrow whose releases are authorized according to `policy`
+    /// by the depositor and/or a third-party `approver`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_escrow_with_approver(
+        env: Env,
+        escrow_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        milestones: Vec<Milestone>,
+        deadline: u64,
+        approver: Address,
+        policy: ApprovalPolicy,
+    ) -> Result<(), Error> {
+        depositor.require_auth();
+        ensure_not_paused(&env)?;
+
+        if approver == depositor || approver == recipient {
+            return Err(Error::InvalidApprover);
+        }
+        // Threshold escrows need a signer set; see `create_multisig_escrow`
+        if policy == ApprovalPolicy::Threshold {
+            return Err(Error::InvalidThreshold);
+        }
+
+        let spec = EscrowSpec {
+            escrow_id,
+            recipient,
+            token_address,
+            milestones,
+            deadline,
+        };
+        let total_amount = validate_escrow_spec(&env, &depositor, &spec, 0)?;
+        store_new_escrow(
+            &env,
+            &depositor,
+            &spec,
+            total_amount,
+            EscrowStatus::Created,
+            EscrowExtras {
+                approver: OptionalAddress::Some(approver.clone()),
+                approval_policy: policy,
+                ..EscrowExtras::new(&env)
+            },
+        );
+
         env.events().publish(
             (
                 Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "EscrowCompleted"),
+                Symbol::new(&env, "ApproverAssigned"),
                 escrow_id,
             ),
-            (),
+            (approver, policy),
         );
 
         Ok(())
     }
-}
-
-fn get_storage_key(escrow_id: u64) -> (Symbol, u64) {
-    (symbol_short!("escrow"), escrow_id)
-}
 
-fn ensure_not_paused(env: &Env) -> Result<(), Error> {
+    /// Creates an escrow whose milestones are released once `threshold` of
+    /// `approvers` have called `approve_milestone`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_multisig_escrow(
+        env: Env,
+        escrow_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        milestones: Vec<Milestone>,
+        deadline: u64,
+        approvers: Vec<Address>,
+        threshold: u32,
+    ) -> Result<(), Error> {
+        depositor.require_auth();
+        ensure_not_paused(&env)?;
+
+        validate_approvers(&recipient, &approvers, threshold)?;
+        let spec = EscrowSpec {
+            escrow_id,
+            recipient,
+            token_address,
+            milestones,
+            deadline,
+        };
+        let total_amount = validate_escrow_spec(&env, &depositor, &spec, 0)?;
+        store_new_escrow(
+            &env,
+            &depositor,
+            &spec,
+            total_amount,
+            EscrowStatus::Created,
+            EscrowExtras {
+                approval_policy: ApprovalPolicy::Threshold,
+                approvers,
+                approval_threshold: threshold,
+                ..EscrowExtras::new(&env)
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Records `approver`'s approval of a milestone and releases it as soon
+    /// as the threshold is met.
+    pub fn approve_milestone(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        approver: Address,
+    ) -> Result<(), Error> {
+        ensure_not_paused(&env)?;
+        approver.require_auth();
+
+        let escrow = get_threshold_escrow(&env, escrow_id, milestone_index, &approver)?;
+
+        let mut approvals = get_approvals(&env, escrow_id, milestone_index);
+        if approvals.contains(&approver) {
+            return Err(Error::AlreadyApproved);
+        }
+        approvals.push_back(approver.clone());
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "MilestoneApproved"),
+                escrow_id,
+                milestone_index,
+            ),
+            (approver, approvals.len()),
+        );
+
+        if approvals.len() >= escrow.approval_threshold {
+            env.storage()
+                .persistent()
+                .remove(&approvals_key(escrow_id, milestone_index));
+            release_milestone(&env, escrow_id, milestone_index, Releaser::Approvals)
+        } else {
+            set_approvals(&env, escrow_id, milestone_index, &approvals);
+            Ok(())
+        }
+    }
+
+    /// Withdraws an approval that has not led to a release yet.
+    pub fn revoke_approval(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        approver: Address,
+    ) -> Result<(), Error> {
+        ensure_not_paused(&env)?;
+        approver.require_auth();
+
+        get_threshold_escrow(&env, escrow_id, milestone_index, &approver)?;
+
+        let mut approvals = get_approvals(&env, escrow_id, milestone_index);
+        let position = approvals
+            .first_index_of(&approver)
+            .ok_or(Error::ApprovalNotFound)?;
+        approvals.remove(position);
+        set_approvals(&env, escrow_id, milestone_index, &approvals);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "ApprovalRevoked"),
+                escrow_id,
+                milestone_index,
+            ),
+            (approver, approvals.len()),
+        );
+
+        Ok(())
+    }
+
+    /// Approvers currently signed off on a pending milestone.
+    pub fn get_approvals(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<Vec<Address>, Error> {
+        let escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&get_storage_key(escrow_id))
+            .ok_or(Error::EscrowNotFound)?;
+        if milestone_index >= escrow.milestones.len() {
+            return Err(Error::MilestoneNotFound);
+        }
+        Ok(get_approvals(&env, escrow_id, milestone_index))
+    }
+
+    /// Creates an escrow whose milestones can also be released or disputed by
+    /// `oracle` through `attest_milestone`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_oracle_escrow(
+        env: Env,
+        escrow_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        milestones: Vec<Milestone>,
+        deadline: u64,
+        oracle: Address,
+    ) -> Result<(), Error> {
+        depositor.require_auth();
+        ensure_not_paused(&env)?;
+
+        if oracle == depositor || oracle == recipient {
+            return Err(Error::InvalidApprover);
+        }
+
+        let spec = EscrowSpec {
+            escrow_id,
+            recipient,
+            token_address,
+            milestones,
+            deadline,
+        };
+        let total_amount = validate_escrow_spec(&env, &depositor, &spec, 0)?;
+        store_new_escrow(
+            &env,
+            &depositor,
+            &spec,
+            total_amount,
+            EscrowStatus::Created,
+            EscrowExtras {
+                oracle: OptionalAddress::Some(oracle.clone()),
+                ..EscrowExtras::new(&env)
+            },
+        );
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "OracleAssigned"),
+                escrow_id,
+            ),
+            oracle,
+        );
+
+        Ok(())
+    }
+
+    /// Stores a milestone layout the owner can reuse with
+    /// `create_escrow_from_template`. Returns the new template id.
+    pub fn register_template(
+        env: Env,
+        owner: Address,
+        token_address: Address,
+        milestones: Vec<TemplateMilestone>,
+        deadline_offset: u64,
+        fee_payer: FeePayer,
+    ) -> Result<u64, Error> {
+        owner.require_auth();
+        ensure_not_paused(&env)?;
+
+        if milestones.is_empty() {
+            return Err(Error::NoMilestones);
+        }
+        if milestones.len() > get_limits(&env).max_milestones {
+            return Err(Error::VectorTooLarge);
+        }
+        let mut total_bps: u32 = 0;
+        for milestone in milestones.iter() {
+            if milestone.share_bps == 0 {
+                return Err(Error::InvalidSplits);
+            }
+            if milestone.title.len() == 0 {
+                return Err(Error::EmptyMilestoneTitle);
+            }
+            if milestone.title.len() > MAX_TITLE_LEN {
+                return Err(Error::MilestoneTitleTooLong);
+            }
+            total_bps = total_bps
+                .checked_add(milestone.share_bps)
+                .ok_or(Error::InvalidSplits)?;
+        }
+        if total_bps as i128 != BPS_DENOMINATOR {
+            return Err(Error::InvalidSplits);
+        }
+        if deadline_offset == 0 {
+            return Err(Error::InvalidDeadline);
+        }
+        if !get_allowed_tokens(&env).contains_key(token_address.clone()) {
+            return Err(Error::TokenNotAllowed);
+        }
+
+        let template_id: u64 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("tpl_next"))
+            .unwrap_or(1);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("tpl_next"), &(template_id + 1));
+
+        let template = EscrowTemplate {
+            owner: owner.clone(),
+            token_address,
+            milestones,
+            deadline_offset,
+            fee_payer,
+        };
+        let storage_key = get_template_key(template_id);
+        env.storage().persistent().set(&storage_key, &template);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "TemplateRegistered"),
+                template_id,
+            ),
+            owner,
+        );
+
+        Ok(template_id)
+    }
+
+    /// Fails with `EscrowNotFound` when no template has this id.
+    pub fn get_template(env: Env, template_id: u64) -> Result<EscrowTemplate, Error> {
+        get_template(&env, template_id)
+    }
+
+    /// Creates an escrow for the template owner, splitting `total` by the
+    /// template shares. Rounding dust goes to the last milestone.
+    pub fn create_escrow_from_template(
+        env: Env,
+        escrow_id: u64,
+        template_id: u64,
+        total: i128,
+        recipient: Address,
+    ) -> Result<(), Error> {
+        ensure_not_paused(&env)?;
+
+        let template = get_template(&env, template_id)?;
+        let depositor = template.owner.clone();
+        depositor.require_auth();
+
+        let spec = EscrowSpec {
+            escrow_id,
+            recipient,
+            token_address: template.token_address.clone(),
+            milestones: expand_template(&env, &template, total)?,
+            deadline: env
+                .ledger()
+                .timestamp()
+                .saturating_add(template.deadline_offset),
+        };
+        let total_amount = validate_escrow_spec(&env, &depositor, &spec, 0)?;
+        store_new_escrow(
+            &env,
+            &depositor,
+            &spec,
+            total_amount,
+            EscrowStatus::Created,
+            EscrowExtras::new(&env),
+        );
+
+        Ok(())
+    }
+
+    /// Issues an invoice: a `Created` escrow without a depositor. Whoever
+    /// pays it through `pay_invoice` becomes the depositor.
+    pub fn create_invoice(
+        env: Env,
+        escrow_id: u64,
+        recipient: Address,
+        milestones: Vec<Milestone>,
+        token_address: Address,
+        deadline: u64,
+        payer: Option<Address>,
+    ) -> Result<(), Error> {
+        recipient.require_auth();
+        ensure_not_paused(&env)?;
+
+        if let Some(payer) = &payer {
+            ensure_not_self_dealing(payer, &recipient, &milestones)?;
+        }
+        let spec = EscrowSpec {
+            escrow_id,
+            recipient,
+            token_address,
+            milestones,
+            deadline,
+        };
+        let total_amount = validate_escrow_terms(&env, &spec)?;
+
+        let payer = match payer {
+            Some(payer) => OptionalAddress::Some(payer),
+            None => OptionalAddress::None,
+        };
+        let escrow = new_escrow(
+            &env,
+            OptionalAddress::None,
+            &spec,
+            total_amount,
+            EscrowStatus::Created,
+            EscrowExtras {
+                payer: payer.clone(),
+                ..EscrowExtras::new(&env)
+            },
+        );
+        let storage_key = get_storage_key(escrow_id);
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "InvoiceCreated"),
+                escrow_id,
+            ),
+            (spec.recipient, spec.token_address, total_amount, payer),
+        );
+
+        Ok(())
+    }
+
+    /// Funds an invoice in full. The payer becomes the depositor and the
+    /// escrow turns `Active`.
+    pub fn pay_invoice(env: Env, escrow_id: u64, payer: Address) -> Result<(), Error> {
+        let storage_key = get_storage_key(escrow_id);
+        payer.require_auth();
+        ensure_not_paused(&env)?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+
+        if escrow.status != EscrowStatus::Created {
+            return Err(Error::EscrowAlreadyFunded);
+        }
+        if escrow.depositor != OptionalAddress::None {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        if let Some(designated) = escrow.payer.to_option() {
+            if designated != payer {
+                return Err(Error::UnauthorizedAccess);
+            }
+        }
+        ensure_not_self_dealing(&payer, &escrow.recipient, &escrow.milestones)?;
+        ensure_active_slot_available(&env, &payer, 0)?;
+        // The terms may have gone stale while the invoice was outstanding
+        if escrow.deadline <= env.ledger().timestamp() {
+            return Err(Error::InvalidDeadline);
+        }
+        validate_due_dates(&env, &escrow.milestones, escrow.deadline)?;
+
+        let totals = token_totals(&env, &escrow.token_address, &escrow.milestones)?;
+        for (token_address, amount) in totals.iter() {
+            pull_funds(&env, &token_address, &payer, amount, FundingSource::Direct)?;
+        }
+
+        escrow.depositor = OptionalAddress::Some(payer.clone());
+        escrow.funded_amount = escrow.total_amount;
+        escrow.status = EscrowStatus::Active;
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+        claim_active_slot(&env, &payer);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "InvoicePaid"),
+                escrow_id,
+            ),
+            payer,
+        );
+        // Standardized Event
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "EscrowFunded"),
+                escrow_id,
+            ),
+            escrow.total_amount,
+        );
+
+        Ok(())
+    }
+
+    /// Withdraws an unpaid invoice; the escrow ends up `Cancelled`.
+    pub fn cancel_invoice(env: Env, escrow_id: u64) -> Result<(), Error> {
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        escrow.recipient.require_auth();
+
+        if escrow.status != EscrowStatus::Created || escrow.depositor != OptionalAddress::None {
+            return Err(Error::InvalidEscrowStatus);
+        }
+
+        escrow.status = EscrowStatus::Cancelled;
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "InvoiceCancelled"),
+                escrow_id,
+            ),
+            escrow.recipient,
+        );
+
+        Ok(())
+    }
+
+    /// Creates an escrow that pays several recipients. Every payout, after
+    /// fees, is divided by `share_bps`, which must sum to 10000.
+    pub fn create_split_escrow(
+        env: Env,
+        escrow_id: u64,
+        depositor: Address,
+        splits: Vec<PayoutSplit>,
+        token_address: Address,
+        milestones: Vec<Milestone>,
+        deadline: u64,
+    ) -> Result<(), Error> {
+        depositor.require_auth();
+        ensure_not_paused(&env)?;
+
+        validate_splits(&depositor, &splits)?;
+        let spec = EscrowSpec {
+            escrow_id,
+            recipient: splits.get(0).ok_or(Error::InvalidSplits)?.recipient,
+            token_address,
+            milestones,
+            deadline,
+        };
+        let total_amount = validate_escrow_spec(&env, &depositor, &spec, 0)?;
+        store_new_escrow(
+            &env,
+            &depositor,
+            &spec,
+            total_amount,
+            EscrowStatus::Created,
+            EscrowExtras {
+                splits,
+                ..EscrowExtras::new(&env)
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Creates an escrow whose `amount` vests linearly between `start_time`
+    /// and `end_time`. It is funded like any other escrow and paid out with
+    /// `claim_streamed` instead of milestone releases.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream_escrow(
+        env: Env,
+        escrow_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        amount: i128,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<(), Error> {
+        depositor.require_auth();
+        ensure_not_paused(&env)?;
+
+        if start_time >= end_time {
+            return Err(Error::InvalidStreamWindow);
+        }
+
+        // A single milestone carries the amount so funding and refunds
+        // follow the regular escrow accounting
+        let milestones = Vec::from_array(
+            &env,
+            [Milestone {
+                amount,
+                status: MilestoneStatus::Pending,
+                title: String::from_str(&env, "Stream"),
+                content_hash: OptionalHash::None,
+                token: OptionalAddress::None,
+                due_date: None,
+                recipient: OptionalAddress::None,
+                hashlock: OptionalHash::None,
+            }],
+        );
+        let spec = EscrowSpec {
+            escrow_id,
+            recipient,
+            token_address,
+            milestones,
+            deadline: end_time,
+        };
+        let total_amount = validate_escrow_spec(&env, &depositor, &spec, 0)?;
+        store_new_escrow(
+            &env,
+            &depositor,
+            &spec,
+            total_amount,
+            EscrowStatus::Created,
+            EscrowExtras {
+                release_mode: ReleaseMode::Stream(StreamSchedule {
+                    start_time,
+                    end_time,
+                    paused_at: None,
+                }),
+                ..EscrowExtras::new(&env)
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Pays the recipient everything vested and funded but not yet claimed.
+    pub fn claim_streamed(env: Env, escrow_id: u64) -> Result<i128, Error> {
+        let storage_key = get_storage_key(escrow_id);
+        ensure_not_paused(&env)?;
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        escrow.recipient.require_auth();
+
+        let ReleaseMode::Stream(schedule) = escrow.release_mode else {
+            return Err(Error::WrongReleaseMode);
+        };
+        if !matches!(
+            escrow.status,
+            EscrowStatus::Active | EscrowStatus::PartiallyFunded
+        ) {
+            return Err(Error::EscrowNotActive);
+        }
+
+        let amount = streamed_amount(&env, &escrow, &schedule)?;
+        if amount == 0 {
+            return Err(Error::NothingToClaim);
+        }
+        let config = Self::get_config(env.clone())?;
+        let (payout, fee) = pay_streamed(&env, &mut escrow, amount, Some(config))?;
+        let completed = mark_completed_if_all_settled(&mut escrow);
+
+        env.storage().persistent().set(&storage_key, &escrow);
+        env.storage()
+            .persistent()
+            .extend_ttl(&storage_key, 100, 2_000_000);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "StreamClaimed"),
+                escrow_id,
+            ),
+            (payout, fee),
+        );
+        if completed {
+            finish_completed_escrow(&env, escrow_id, &escrow.depositor()?);
+        }
+
+        Ok(payout)
+    }
+
+    /// Amount `claim_streamed` would pay out now, before fees.
+    pub fn get_streamed_amount(env: Env, escrow_id: u64) -> Result<i128, Error> {
+        let escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&get_storage_key(escrow_id))
+            .ok_or(Error::EscrowNotFound)?;
+        let ReleaseMode::Stream(schedule) = escrow.release_mode else {
+            return Err(Error::WrongReleaseMode);
+        };
+        streamed_amount(&env, &escrow, &schedule)
+    }
+
+    /// Creates a recurring escrow and pulls `periods` worth of funds up front.
+    /// The first period unlocks one `interval` after creation.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_subscription(
+        env: Env,
+        subscription_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        amount_per_period: i128,
+        interval: u64,
+        periods: u32,
+    ) -> Result<(), Error> {
+        ensure_not_paused(&env)?;
+        depositor.require_auth();
+
+        if depositor == recipient {
+            return Err(Error::SelfDealing);
+        }
+        if amount_per_period <= 0 || periods == 0 {
+            return Err(Error::ZeroAmount);
+        }
+        if interval == 0 {
+            return Err(Error::InvalidInterval);
+        }
+
+        let storage_key = get_subscription_key(subscription_id);
+        if env.storage().persistent().has(&storage_key) {
+            return Err(Error::EscrowAlreadyExists);
+        }
+
+        let total_amount = periods_amount(amount_per_period, periods)?;
+        let limits = get_allowed_tokens(&env)
+            .get(token_address.clone())
+            .ok_or(Error::TokenNotAllowed)?;
+        if total_amount < limits.min_amount || total_amount > limits.max_amount {
+            return Err(Error::EscrowAmountOutOfRange);
+        }
+        ensure_token_contract(&env, &token_address)?;
+
+        pull_funds(
+            &env,
+            &token_address,
+            &depositor,
+            total_amount,
+            FundingSource::Direct,
+        )?;
+
+        let subscription = Subscription {
+            depositor,
+            recipient,
+            token_address,
+            amount_per_period,
+            interval,
+            start_time: env.ledger().timestamp(),
+            funded_periods: periods,
+            claimed_periods: 0,
+            status: SubscriptionStatus::Active,
+        };
+        save_subscription(&env, subscription_id, &subscription);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "SubscriptionCreated"),
+                subscription_id,
+            ),
+            (amount_per_period, interval, periods),
+        );
+
+        Ok(())
+    }
+
+    /// Funds `periods` more periods of an active subscription.
+    pub fn top_up_subscription(env: Env, subscription_id: u64, periods: u32) -> Result<(), Error> {
+        ensure_not_paused(&env)?;
+
+        let mut subscription = get_subscription(&env, subscription_id)?;
+        subscription.depositor.require_auth();
+
+        if subscription.status != SubscriptionStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+        if periods == 0 {
+            return Err(Error::ZeroAmount);
+        }
+
+        // Intervals that passed while nothing was funded are not owed
+        let now = env.ledger().timestamp();
+        let funded_span =
+            (subscription.funded_periods as u64).saturating_mul(subscription.interval);
+        if now.saturating_sub(subscription.start_time) > funded_span {
+            subscription.start_time = now - funded_span;
+        }
+
+        subscription.funded_periods = subscription
+            .funded_periods
+            .checked_add(periods)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        // The allowlist bound applies to everything the subscription funds
+        let total_amount =
+            periods_amount(subscription.amount_per_period, subscription.funded_periods)?;
+        let limits = get_allowed_tokens(&env)
+            .get(subscription.token_address.clone())
+            .ok_or(Error::TokenNotAllowed)?;
+        if total_amount > limits.max_amount {
+            return Err(Error::EscrowAmountOutOfRange);
+        }
+
+        let amount = periods_amount(subscription.amount_per_period, periods)?;
+        pull_funds(
+            &env,
+            &subscription.token_address,
+            &subscription.depositor,
+            amount,
+            FundingSource::Direct,
+        )?;
+        save_subscription(&env, subscription_id, &subscription);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "SubscriptionToppedUp"),
+                subscription_id,
+            ),
+            (periods, subscription.funded_periods),
+        );
+
+        Ok(())
+    }
+
+    /// Pays the recipient every unlocked, unclaimed period at once. The
+    /// protocol fee is taken from each claim as it is for milestone releases.
+    pub fn claim_subscription(env: Env, subscription_id: u64) -> Result<i128, Error> {
+        ensure_not_paused(&env)?;
+
+        let mut subscription = get_subscription(&env, subscription_id)?;
+        subscription.recipient.require_auth();
+
+        let claimable = claimable_periods(&env, &subscription);
+        if claimable == 0 {
+            return Err(Error::NothingToClaim);
+        }
+
+        let amount = periods_amount(subscription.amount_per_period, claimable)?;
+        let (treasury, fee_bps) = Self::get_config(env.clone())?;
+        let fee = calculate_fee(amount, fee_bps)?;
+        let payout = amount
+            .checked_sub(fee)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+
+        push_funds(
+            &env,
+            &subscription.token_address,
+            &subscription.recipient,
+            payout,
+        )?;
+        if fee > 0 {
+            push_funds(&env, &subscription.token_address, &treasury, fee)?;
+        }
+
+        subscription.claimed_periods += claimable;
+        if subscription.status == SubscriptionStatus::Stopped
+            && subscription.claimed_periods == subscription.funded_periods
+        {
+            subscription.status = SubscriptionStatus::Completed;
+        }
+        save_subscription(&env, subscription_id, &subscription);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "SubscriptionClaimed"),
+                subscription_id,
+            ),
+            (claimable, payout, fee),
+        );
+
+        Ok(payout)
+    }
+
+    /// Stops future periods and refunds the ones that have not unlocked yet.
+    /// Periods already unlocked remain claimable by the recipient.
+    pub fn stop_subscription(env: Env, subscription_id: u64) -> Result<i128, Error> {
+        ensure_not_paused(&env)?;
+
+        let mut subscription = get_subscription(&env, subscription_id)?;
+        subscription.depositor.require_auth();
+
+        if subscription.status != SubscriptionStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+
+        let unlocked = unlocked_periods(&env, &subscription);
+        let unused = subscription.funded_periods - unlocked;
+        let refund = periods_amount(subscription.amount_per_period, unused)?;
+        if refund > 0 {
+            push_funds(
+                &env,
+                &subscription.token_address,
+                &subscription.depositor,
+                refund,
+            )?;
+        }
+
+        subscription.funded_periods = unlocked;
+        subscription.status = if subscription.claimed_periods == unlocked {
+            SubscriptionStatus::Completed
+        } else {
+            SubscriptionStatus::Stopped
+        };
+        save_subscription(&env, subscription_id, &subscription);
+
+        env.events().publish(
+            (
+                Symbol::new(&env, "Vaultix"),
+                Symbol::new(&env, "SubscriptionStopped"),
+                subscription_id,
+            ),
+            (unused, refund),
+        );
+
+        Ok(refund)
+    }
+
+    pub fn get_subscription(env: Env, subscription_id: u64) -> Result<Subscription, Error> {
+        get_subscription(&env, subscription_id)
+    }
+
+    /// Periods the recipient could claim right now.
+    pub fn get_claimable_periods(env: Env, subscription_id: u64) -> Result<u32, Error> {
+        let subscription = get_subscription(&env, subscription_id)?;
+        Ok(claimable_periods(&env, &subscription))
+    }
+}
+
+fn get_storage_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("escrow"), escrow_id)
+}
+
+fn get_subscription_key(subscription_id: u64) -> (Symbol, u64) {
+    (symbol_short!("sub"), subscription_id)
+}
+
+fn get_subscription(env: &Env, subscription_id: u64) -> Result<Subscription, Error> {
+    env.storage()
+        .persistent()
+        .get(&get_subscription_key(subscription_id))
+        .ok_or(Error::SubscriptionNotFound)
+}
+
+fn save_subscription(env: &Env, subscription_id: u64, subscription: &Subscription) {
+    let storage_key = get_subscription_key(subscription_id);
+    env.storage().persistent().set(&storage_key, subscription);
+    env.storage()
+        .persistent()
+        .extend_ttl(&storage_key, 100, 2_000_000);
+}
+
+fn periods_amount(amount_per_period: i128, periods: u32) -> Result<i128, Error> {
+    amount_per_period
+        .checked_mul(periods as i128)
+        .ok_or(Error::InvalidMilestoneAmount)
+}
+
+/// Funded periods whose interval has fully elapsed.
+fn unlocked_periods(env: &Env, subscription: &Subscription) -> u32 {
+    let elapsed = env
+        .ledger()
+        .timestamp()
+        .saturating_sub(subscription.start_time);
+    let periods = elapsed / subscription.interval;
+    periods.min(subscription.funded_periods as u64) as u32
+}
+
+fn claimable_periods(env: &Env, subscription: &Subscription) -> u32 {
+    unlocked_periods(env, subscription) - subscription.claimed_periods
+}
+
+fn ensure_not_paused(env: &Env) -> Result<(), Error> {
     let state: ContractState = env
         .storage()
         .instance()
@@ -755,19 +2604,435 @@ fn ensure_not_paused(env: &Env) -> Result<(), Error> {
     Ok(())
 }
 
-fn admin_storage_key() -> Symbol {
-    symbol_short!("admin")
+fn admin_storage_key() -> Symbol {
+    symbol_short!("admin")
+}
+
+fn limits_key() -> Symbol {
+    symbol_short!("limits")
+}
+
+fn default_limits() -> Limits {
+    Limits {
+        max_milestones: DEFAULT_MAX_MILESTONES,
+        max_deadline_horizon: u64::MAX,
+        max_active_escrows: u32::MAX,
+    }
+}
+
+fn get_limits(env: &Env) -> Limits {
+    env.storage()
+        .instance()
+        .get(&limits_key())
+        .unwrap_or_else(default_limits)
+}
+
+fn active_escrows_key(depositor: &Address) -> (Symbol, Address) {
+    (symbol_short!("active"), depositor.clone())
+}
+
+/// Escrows of `depositor` that have not reached a terminal state yet.
+fn get_active_escrows(env: &Env, depositor: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&active_escrows_key(depositor))
+        .unwrap_or(0)
+}
+
+fn set_active_escrows(env: &Env, depositor: &Address, count: u32) {
+    let key = active_escrows_key(depositor);
+    env.storage().persistent().set(&key, &count);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+}
+
+fn claim_active_slot(env: &Env, depositor: &Address) {
+    let count = get_active_escrows(env, depositor).saturating_add(1);
+    set_active_escrows(env, depositor, count);
+}
+
+/// Saturates at zero so escrows created before the counter existed can
+/// still be closed.
+fn release_active_slot(env: &Env, depositor: &Address) {
+    let count = get_active_escrows(env, depositor).saturating_sub(1);
+    set_active_escrows(env, depositor, count);
+}
+
+fn allowed_tokens_key() -> Symbol {
+    symbol_short!("tokens")
+}
+
+fn get_allowed_tokens(env: &Env) -> Map<Address, TokenLimits> {
+    env.storage()
+        .instance()
+        .get(&allowed_tokens_key())
+        .unwrap_or_else(|| Map::new(env))
+}
+
+fn get_admin(env: &Env) -> Result<Address, Error> {
+    env.storage()
+        .persistent()
+        .get(&admin_storage_key())
+        .ok_or(Error::AdminNotInitialized)
+}
+
+fn fund_escrow(env: &Env, escrow_id: u64, source: FundingSource) -> Result<(), Error> {
+    let storage_key = get_storage_key(escrow_id);
+    ensure_not_paused(env)?;
+
+    let mut escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    escrow.depositor()?.require_auth();
+
+    if escrow.status != EscrowStatus::Created && escrow.status != EscrowStatus::PartiallyFunded {
+        return Err(Error::EscrowAlreadyFunded);
+    }
+
+    // Partially funded escrows are topped up with whatever is still missing
+    let funded = funded_by_token(env, &escrow)?;
+    let totals = token_totals(env, &escrow.token_address, &escrow.milestones)?;
+    for (token_address, total) in totals.iter() {
+        let remaining = total
+            .checked_sub(funded.get(token_address.clone()).unwrap_or(0))
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        if remaining > 0 {
+            pull_funds(env, &token_address, &escrow.depositor()?, remaining, source)?;
+        }
+    }
+
+    escrow.funded_amount = escrow.total_amount;
+    escrow.status = EscrowStatus::Active;
+    env.storage().persistent().set(&storage_key, &escrow);
+    env.storage()
+        .persistent()
+        .extend_ttl(&storage_key, 100, 2_000_000);
+
+    // Standardized Event
+    env.events().publish(
+        (
+            Symbol::new(env, "Vaultix"),
+            Symbol::new(env, "EscrowFunded"),
+            escrow_id,
+        ),
+        escrow.total_amount,
+    );
+
+    Ok(())
+}
+
+/// Moves `amount` from `from` into the contract. Balance (and allowance, when
+/// pulling via `transfer_from`) is checked up front so a shortfall surfaces as
+/// `InsufficientBalance` instead of a trap inside the token contract. The
+/// contract's own balance must grow by exactly `amount`, which rejects
+/// fee-on-transfer and misreporting tokens.
+fn pull_funds(
+    env: &Env,
+    token_address: &Address,
+    from: &Address,
+    amount: i128,
+    source: FundingSource,
+) -> Result<(), Error> {
+    let contract = env.current_contract_address();
+    let token_client = token::Client::new(env, token_address);
+
+    if token_client.balance(from) < amount {
+        return Err(Error::InsufficientBalance);
+    }
+
+    let balance_before = token_client.balance(&contract);
+    match source {
+        FundingSource::Allowance => {
+            if token_client.allowance(from, &contract) < amount {
+                return Err(Error::InsufficientBalance);
+            }
+            token_client.transfer_from(&contract, from, &contract, &amount);
+        }
+        FundingSource::Direct => token_client.transfer(from, &contract, &amount),
+    }
+    let received = token_client
+        .balance(&contract)
+        .checked_sub(balance_before)
+        .ok_or(Error::TokenTransferFailed)?;
+    if received != amount {
+        return Err(Error::TokenTransferFailed);
+    }
+
+    adjust_expected_holdings(env, token_address, amount)
+}
+
+/// Sends escrowed funds out of the contract and removes them from the
+/// holdings ledger.
+fn push_funds(env: &Env, token_address: &Address, to: &Address, amount: i128) -> Result<(), Error> {
+    send_tokens(env, token_address, to, amount)?;
+
+    let delta = amount.checked_neg().ok_or(Error::InvalidMilestoneAmount)?;
+    adjust_expected_holdings(env, token_address, delta)
+}
+
+/// Transfers `amount` out of the contract, verifying that the contract's
+/// balance drops by exactly that much. Does not touch the ledger.
+fn send_tokens(
+    env: &Env,
+    token_address: &Address,
+    to: &Address,
+    amount: i128,
+) -> Result<(), Error> {
+    let contract = env.current_contract_address();
+    let token_client = token::Client::new(env, token_address);
+
+    let balance_before = token_client.balance(&contract);
+    token_client.transfer(&contract, to, &amount);
+    let sent = balance_before
+        .checked_sub(token_client.balance(&contract))
+        .ok_or(Error::TokenTransferFailed)?;
+    if sent != amount {
+        return Err(Error::TokenTransferFailed);
+    }
+    Ok(())
+}
+
+/// Contract balance above liabilities; negative if the contract is insolvent.
+fn get_surplus(env: &Env, token_address: &Address) -> Result<i128, Error> {
+    let token_client = token::Client::new(env, token_address);
+    token_client
+        .balance(&env.current_contract_address())
+        .checked_sub(get_expected_holdings(env, token_address))
+        .ok_or(Error::InvalidMilestoneAmount)
 }
 
-fn get_admin(env: &Env) -> Result<Address, Error> {
+fn holdings_key(token_address: &Address) -> (Symbol, Address) {
+    (symbol_short!("held"), token_address.clone())
+}
+
+/// What the contract should hold in `token_address` according to its own
+/// bookkeeping of deposits and payouts.
+fn get_expected_holdings(env: &Env, token_address: &Address) -> i128 {
     env.storage()
         .persistent()
-        .get(&admin_storage_key())
-        .ok_or(Error::AdminNotInitialized)
+        .get(&holdings_key(token_address))
+        .unwrap_or(0)
+}
+
+fn adjust_expected_holdings(env: &Env, token_address: &Address, delta: i128) -> Result<(), Error> {
+    let key = holdings_key(token_address);
+    let updated = get_expected_holdings(env, token_address)
+        .checked_add(delta)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+    env.storage().persistent().set(&key, &updated);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+    Ok(())
+}
+
+/// Checks one escrow against the configured limits. `pending` is the number
+/// of escrows the same call creates for `depositor` before this one.
+fn validate_escrow_spec(
+    env: &Env,
+    depositor: &Address,
+    spec: &EscrowSpec,
+    pending: u32,
+) -> Result<i128, Error> {
+    ensure_not_self_dealing(depositor, &spec.recipient, &spec.milestones)?;
+    let total_amount = validate_escrow_terms(env, spec)?;
+    ensure_active_slot_available(env, depositor, pending)?;
+    Ok(total_amount)
+}
+
+/// Checks of a new escrow that do not depend on who deposits, shared with
+/// invoices whose payer is not known yet.
+fn validate_escrow_terms(env: &Env, spec: &EscrowSpec) -> Result<i128, Error> {
+    let storage_key = get_storage_key(spec.escrow_id);
+    if env.storage().persistent().has(&storage_key) {
+        return Err(Error::EscrowAlreadyExists);
+    }
+
+    let limits = get_limits(env);
+    let total_amount = validate_milestones(&spec.milestones, limits.max_milestones)?;
+
+    let now = env.ledger().timestamp();
+    let latest_deadline = now.saturating_add(limits.max_deadline_horizon);
+    if spec.deadline <= now || spec.deadline > latest_deadline {
+        return Err(Error::InvalidDeadline);
+    }
+    validate_due_dates(env, &spec.milestones, spec.deadline)?;
+
+    let allowed_tokens = get_allowed_tokens(env);
+    if !allowed_tokens.contains_key(spec.token_address.clone()) {
+        return Err(Error::TokenNotAllowed);
+    }
+    for (token_address, amount) in token_totals(env, &spec.token_address, &spec.milestones)?.iter()
+    {
+        let limits = allowed_tokens
+            .get(token_address.clone())
+            .ok_or(Error::TokenNotAllowed)?;
+        if amount < limits.min_amount || amount > limits.max_amount {
+            return Err(Error::EscrowAmountOutOfRange);
+        }
+        ensure_token_contract(env, &token_address)?;
+    }
+
+    Ok(total_amount)
+}
+
+fn ensure_not_self_dealing(
+    depositor: &Address,
+    recipient: &Address,
+    milestones: &Vec<Milestone>,
+) -> Result<(), Error> {
+    if depositor == recipient {
+        return Err(Error::SelfDealing);
+    }
+    for milestone in milestones.iter() {
+        if milestone.recipient.to_option().as_ref() == Some(depositor) {
+            return Err(Error::SelfDealing);
+        }
+    }
+    Ok(())
+}
+
+fn ensure_active_slot_available(env: &Env, depositor: &Address, pending: u32) -> Result<(), Error> {
+    let limits = get_limits(env);
+    if get_active_escrows(env, depositor).saturating_add(pending) >= limits.max_active_escrows {
+        return Err(Error::TooManyActiveEscrows);
+    }
+    Ok(())
+}
+
+/// Validates a whole batch without writing anything. The outer error covers
+/// batch-level problems; the inner one names the first offending spec.
+fn validate_batch(
+    env: &Env,
+    depositor: &Address,
+    specs: &Vec<EscrowSpec>,
+) -> Result<Result<Vec<i128>, (u32, Error)>, Error> {
+    if specs.len() > MAX_BATCH_SIZE {
+        return Err(Error::VectorTooLarge);
+    }
+
+    let mut totals = Vec::new(env);
+    for (index, spec) in specs.iter().enumerate() {
+        for earlier in specs.iter().take(index) {
+            if earlier.escrow_id == spec.escrow_id {
+                return Ok(Err((index as u32, Error::EscrowAlreadyExists)));
+            }
+        }
+        match validate_escrow_spec(env, depositor, &spec, index as u32) {
+            Ok(total) => totals.push_back(total),
+            Err(error) => return Ok(Err((index as u32, error))),
+        }
+    }
+    Ok(Ok(totals))
+}
+
+fn store_new_escrow(
+    env: &Env,
+    depositor: &Address,
+    spec: &EscrowSpec,
+    total_amount: i128,
+    status: EscrowStatus,
+    extras: EscrowExtras,
+) {
+    let escrow = new_escrow(
+        env,
+        OptionalAddress::Some(depositor.clone()),
+        spec,
+        total_amount,
+        status,
+        extras,
+    );
+    let storage_key = get_storage_key(spec.escrow_id);
+    env.storage().persistent().set(&storage_key, &escrow);
+    env.storage()
+        .persistent()
+        .extend_ttl(&storage_key, 100, 2_000_000);
+    claim_active_slot(env, depositor);
+
+    // Standardized Event
+    env.events().publish(
+        (
+            Symbol::new(env, "Vaultix"),
+            Symbol::new(env, "EscrowCreated"),
+            spec.escrow_id,
+        ),
+        (
+            depositor.clone(),
+            spec.recipient.clone(),
+            spec.token_address.clone(),
+            total_amount,
+            spec.deadline,
+        ),
+    );
+
+    if status == EscrowStatus::Active {
+        env.events().publish(
+            (
+                Symbol::new(env, "Vaultix"),
+                Symbol::new(env, "EscrowFunded"),
+                spec.escrow_id,
+            ),
+            total_amount,
+        );
+    }
+}
+
+fn new_escrow(
+    env: &Env,
+    depositor: OptionalAddress,
+    spec: &EscrowSpec,
+    total_amount: i128,
+    status: EscrowStatus,
+    extras: EscrowExtras,
+) -> Escrow {
+    let mut initialized_milestones = Vec::new(env);
+    for milestone in spec.milestones.iter() {
+        let mut m = milestone.clone();
+        m.status = MilestoneStatus::Pending;
+        initialized_milestones.push_back(m);
+    }
+
+    Escrow {
+        depositor,
+        recipient: spec.recipient.clone(),
+        token_address: spec.token_address.clone(),
+        total_amount,
+        funded_amount: if status == EscrowStatus::Active {
+            total_amount
+        } else {
+            0
+        },
+        total_released: 0,
+        released_by_token: Map::new(env),
+        refunded_by_token: Map::new(env),
+        milestones: initialized_milestones,
+        status,
+        deadline: spec.deadline,
+        resolution: Resolution::None,
+        release_mode: extras.release_mode,
+        splits: extras.splits,
+        approver: extras.approver,
+        approval_policy: extras.approval_policy,
+        approvers: extras.approvers,
+        approval_threshold: extras.approval_threshold,
+        oracle: extras.oracle,
+        payer: extras.payer,
+    }
+}
+
+/// Guards against allowlisted addresses that no longer (or never did) host a
+/// token contract, which would otherwise only surface at funding time.
+fn ensure_token_contract(env: &Env, token_address: &Address) -> Result<(), Error> {
+    match token::Client::new(env, token_address).try_decimals() {
+        Ok(Ok(_)) => Ok(()),
+        _ => Err(Error::InvalidToken),
+    }
 }
 
-fn validate_milestones(milestones: &Vec<Milestone>) -> Result<i128, Error> {
-    if milestones.len() > 20 {
+fn validate_milestones(milestones: &Vec<Milestone>, max_milestones: u32) -> Result<i128, Error> {
+    if milestones.is_empty() {
+        return Err(Error::NoMilestones);
+    }
+    if milestones.len() > max_milestones {
         return Err(Error::VectorTooLarge);
     }
     let mut total: i128 = 0;
@@ -775,6 +3040,23 @@ fn validate_milestones(milestones: &Vec<Milestone>) -> Result<i128, Error> {
         if milestone.amount <= 0 {
             return Err(Error::ZeroAmount);
         }
+        if milestone.title.len() == 0 {
+            return Err(Error::EmptyMilestoneTitle);
+        }
+        if milestone.title.len() > MAX_TITLE_LEN {
+            return Err(Error::MilestoneTitleTooLong);
+        }
+        // An all-zero hash is what an unset client-side buffer looks like
+        if let OptionalHash::Some(hash) = &milestone.content_hash {
+            if hash.to_array() == [0u8; 32] {
+                return Err(Error::InvalidContentHash);
+            }
+        }
+        if let OptionalHash::Some(hash) = &milestone.hashlock {
+            if hash.to_array() == [0u8; 32] {
+                return Err(Error::InvalidContentHash);
+            }
+        }
         total = total
             .checked_add(milestone.amount)
             .ok_or(Error::InvalidMilestoneAmount)?;
@@ -782,15 +3064,664 @@ fn validate_milestones(milestones: &Vec<Milestone>) -> Result<i128, Error> {
     Ok(total)
 }
 
-fn verify_all_released(milestones: &Vec<Milestone>) -> bool {
+/// Milestone due dates must lie in the future and no later than the escrow
+/// deadline, otherwise the milestone would be reclaimable once funded.
+fn validate_due_dates(env: &Env, milestones: &Vec<Milestone>, deadline: u64) -> Result<(), Error> {
+    let now = env.ledger().timestamp();
+    for milestone in milestones.iter() {
+        if let Some(due_date) = milestone.due_date {
+            if due_date <= now || due_date > deadline {
+                return Err(Error::InvalidDeadline);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn milestone_token(escrow: &Escrow, milestone: &Milestone) -> Address {
+    milestone
+        .token
+        .to_option()
+        .unwrap_or_else(|| escrow.token_address.clone())
+}
+
+fn add_token_amount(
+    amounts: &mut Map<Address, i128>,
+    token_address: Address,
+    amount: i128,
+) -> Result<(), Error> {
+    let current = amounts.get(token_address.clone()).unwrap_or(0);
+    let updated = current
+        .checked_add(amount)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+    amounts.set(token_address, updated);
+    Ok(())
+}
+
+/// Sums milestone amounts per token. Milestones without their own token are
+/// paid in `default_token`.
+fn token_totals(
+    env: &Env,
+    default_token: &Address,
+    milestones: &Vec<Milestone>,
+) -> Result<Map<Address, i128>, Error> {
+    let mut totals = Map::new(env);
+    for milestone in milestones.iter() {
+        let token_address = milestone
+            .token
+            .to_option()
+            .unwrap_or_else(|| default_token.clone());
+        add_token_amount(&mut totals, token_address, milestone.amount)?;
+    }
+    Ok(totals)
+}
+
+/// Amount deposited per token. Partial funding is limited to escrows paid
+/// entirely in `token_address`, so anything short of fully funded sits there.
+fn funded_by_token(env: &Env, escrow: &Escrow) -> Result<Map<Address, i128>, Error> {
+    if escrow.funded_amount == escrow.total_amount {
+        return token_totals(env, &escrow.token_address, &escrow.milestones);
+    }
+    let mut funded = Map::new(env);
+    funded.set(escrow.token_address.clone(), escrow.funded_amount);
+    Ok(funded)
+}
+
+/// What the contract still holds for this escrow per token: deposits minus
+/// releases and milestone refunds.
+fn held_by_token(env: &Env, escrow: &Escrow) -> Result<Map<Address, i128>, Error> {
+    let mut held = Map::new(env);
+    for (token_address, funded) in funded_by_token(env, escrow)?.iter() {
+        let released = escrow
+            .released_by_token
+            .get(token_address.clone())
+            .unwrap_or(0);
+        let refunded = escrow
+            .refunded_by_token
+            .get(token_address.clone())
+            .unwrap_or(0);
+        let remaining = funded
+            .checked_sub(released)
+            .and_then(|amount| amount.checked_sub(refunded))
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        held.set(token_address, remaining);
+    }
+    Ok(held)
+}
+
+/// Time after which a milestone may be reclaimed: its `due_date`, or for
+/// hashlocked milestones without one, the escrow deadline.
+fn milestone_timelock(escrow: &Escrow, milestone: &Milestone) -> Option<u64> {
+    match (milestone.due_date, &milestone.hashlock) {
+        (Some(due_date), _) => Some(due_date),
+        (None, OptionalHash::Some(_)) => Some(escrow.deadline),
+        (None, OptionalHash::None) => None,
+    }
+}
+
+fn is_overdue(env: &Env, escrow: &Escrow, milestone: &Milestone) -> bool {
+    match milestone_timelock(escrow, milestone) {
+        Some(timelock) => {
+            milestone.status == MilestoneStatus::Pending && env.ledger().timestamp() > timelock
+        }
+        None => false,
+    }
+}
+
+/// Whether a pending, funded hashlocked milestone can still be claimed with
+/// its preimage.
+fn has_live_hashlock(env: &Env, escrow: &Escrow) -> bool {
+    escrow
+        .milestones
+        .iter()
+        .enumerate()
+        .any(|(index, milestone)| {
+            milestone.hashlock != OptionalHash::None
+                && milestone.status == MilestoneStatus::Pending
+                && !is_overdue(env, escrow, &milestone)
+                && ensure_milestone_funded(escrow, index as u32).is_ok()
+        })
+}
+
+/// Milestones are funded in order, so a milestone is releasable once the
+/// funded amount covers it and every milestone before it.
+fn ensure_milestone_funded(escrow: &Escrow, milestone_index: u32) -> Result<(), Error> {
+    let mut required: i128 = 0;
+    for milestone in escrow.milestones.iter().take(milestone_index as usize + 1) {
+        required = required
+            .checked_add(milestone.amount)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+    }
+    if required > escrow.funded_amount {
+        return Err(Error::MilestoneNotFunded);
+    }
+    Ok(())
+}
+
+/// True once no milestone is left to pay out: each one was either released
+/// or refunded to the depositor after its due date.
+fn verify_all_settled(milestones: &Vec<Milestone>) -> bool {
     for milestone in milestones.iter() {
-        if milestone.status != MilestoneStatus::Released {
+        if milestone.status != MilestoneStatus::Released
+            && milestone.status != MilestoneStatus::Refunded
+        {
             return false;
         }
     }
     true
 }
 
+/// Moves an active escrow to `Completed` once every milestone is settled.
+/// Returns whether the transition happened so callers can emit the event
+/// after their own release event.
+fn mark_completed_if_all_settled(escrow: &mut Escrow) -> bool {
+    if escrow.status != EscrowStatus::Active || !verify_all_settled(&escrow.milestones) {
+        return false;
+    }
+    escrow.status = EscrowStatus::Completed;
+    true
+}
+
+/// Shared body of the release entrypoints; `releaser` is checked against the
+/// escrow's approval policy before anything moves.
+fn release_milestone(
+    env: &Env,
+    escrow_id: u64,
+    milestone_index: u32,
+    releaser: Releaser,
+) -> Result<(), Error> {
+    let storage_key = get_storage_key(escrow_id);
+    ensure_not_paused(env)?;
+
+    let mut escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    authorize_release(&escrow, releaser)?;
+
+    if escrow.release_mode != ReleaseMode::Milestones {
+        return Err(Error::WrongReleaseMode);
+    }
+
+    // Completed escrows fall through so re-releases report MilestoneAlreadyReleased
+    if !matches!(
+        escrow.status,
+        EscrowStatus::Active | EscrowStatus::PartiallyFunded | EscrowStatus::Completed
+    ) {
+        return Err(Error::EscrowNotActive);
+    }
+    if milestone_index >= escrow.milestones.len() {
+        return Err(Error::MilestoneNotFound);
+    }
+
+    let mut milestone = escrow
+        .milestones
+        .get(milestone_index)
+        .ok_or(Error::MilestoneNotFound)?;
+    if milestone.status == MilestoneStatus::Released {
+        return Err(Error::MilestoneAlreadyReleased);
+    }
+    if milestone.status == MilestoneStatus::Refunded {
+        return Err(Error::MilestoneRefunded);
+    }
+    ensure_milestone_funded(&escrow, milestone_index)?;
+
+    let (treasury, fee_bps) = VaultixEscrow::get_config(env.clone())?;
+    let fee = calculate_fee(milestone.amount, fee_bps)?;
+    let payout = milestone
+        .amount
+        .checked_sub(fee)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+
+    let token_address = milestone_token(&escrow, &milestone);
+    pay_milestone(env, &escrow, &milestone, &token_address, payout)?;
+
+    if fee > 0 {
+        push_funds(env, &token_address, &treasury, fee)?;
+    }
+
+    milestone.status = MilestoneStatus::Released;
+    escrow.milestones.set(milestone_index, milestone.clone());
+
+    escrow.total_released = escrow
+        .total_released
+        .checked_add(milestone.amount)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+    add_token_amount(
+        &mut escrow.released_by_token,
+        token_address,
+        milestone.amount,
+    )?;
+    let completed = mark_completed_if_all_settled(&mut escrow);
+
+    env.storage().persistent().set(&storage_key, &escrow);
+    env.storage()
+        .persistent()
+        .extend_ttl(&storage_key, 100, 2_000_000);
+
+    // Standardized Event
+    env.events().publish(
+        (
+            Symbol::new(env, "Vaultix"),
+            Symbol::new(env, "MilestoneReleased"),
+            escrow_id,
+            milestone_index,
+        ),
+        (payout, fee),
+    );
+    if completed {
+        finish_completed_escrow(env, escrow_id, &escrow.depositor()?);
+    }
+
+    Ok(())
+}
+
+/// Moves an escrow into dispute: pending milestones are frozen and streams
+/// stop vesting until the admin resolves it.
+fn open_dispute(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &mut Escrow,
+    raised_by: Address,
+) -> Result<(), Error> {
+    if escrow.status == EscrowStatus::Disputed {
+        return Err(Error::AlreadyInDispute);
+    }
+    if !matches!(
+        escrow.status,
+        EscrowStatus::Active | EscrowStatus::PartiallyFunded | EscrowStatus::Created
+    ) {
+        return Err(Error::InvalidEscrowStatus);
+    }
+    // Unpaid invoices hold nothing to dispute
+    if escrow.depositor == OptionalAddress::None {
+        return Err(Error::InvalidEscrowStatus);
+    }
+
+    let mut updated_milestones = Vec::new(env);
+    for milestone in escrow.milestones.iter() {
+        let mut m = milestone.clone();
+        if m.status == MilestoneStatus::Pending {
+            m.status = MilestoneStatus::Disputed;
+        }
+        updated_milestones.push_back(m);
+    }
+
+    escrow.milestones = updated_milestones;
+    escrow.status = EscrowStatus::Disputed;
+    if let ReleaseMode::Stream(mut schedule) = escrow.release_mode {
+        schedule.paused_at = Some(env.ledger().timestamp());
+        escrow.release_mode = ReleaseMode::Stream(schedule);
+    }
+    escrow.resolution = Resolution::None;
+    env.storage()
+        .persistent()
+        .set(&get_storage_key(escrow_id), escrow);
+
+    // Standardized Event
+    env.events().publish(
+        (
+            Symbol::new(env, "Vaultix"),
+            Symbol::new(env, "DisputeRaised"),
+            escrow_id,
+        ),
+        raised_by,
+    );
+
+    Ok(())
+}
+
+fn voucher_key_key(depositor: &Address) -> (Symbol, Address) {
+    (symbol_short!("vkey"), depositor.clone())
+}
+
+fn voucher_nonce_key(depositor: &Address, nonce: u64) -> (Symbol, Address, u64) {
+    (symbol_short!("vnonce"), depositor.clone(), nonce)
+}
+
+fn get_template_key(template_id: u64) -> (Symbol, u64) {
+    (symbol_short!("template"), template_id)
+}
+
+fn get_template(env: &Env, template_id: u64) -> Result<EscrowTemplate, Error> {
+    env.storage()
+        .persistent()
+        .get(&get_template_key(template_id))
+        .ok_or(Error::EscrowNotFound)
+}
+
+/// Turns template shares into milestone amounts for `total`. With
+/// `FeePayer::Depositor` each amount is grossed up at the current fee rate
+/// so the recipient nets its share after the release fee.
+fn expand_template(
+    env: &Env,
+    template: &EscrowTemplate,
+    total: i128,
+) -> Result<Vec<Milestone>, Error> {
+    if total <= 0 {
+        return Err(Error::ZeroAmount);
+    }
+    let fee_bps = match template.fee_payer {
+        FeePayer::Recipient => 0,
+        FeePayer::Depositor => VaultixEscrow::get_config(env.clone())
+            .map(|(_, fee_bps)| fee_bps)
+            .unwrap_or(0),
+    };
+    if fee_bps >= BPS_DENOMINATOR {
+        return Err(Error::InvalidFeeConfiguration);
+    }
+
+    let mut milestones = Vec::new(env);
+    let mut remaining = total;
+    let last_index = template.milestones.len() - 1;
+    for (index, entry) in template.milestones.iter().enumerate() {
+        let net = if index as u32 == last_index {
+            remaining
+        } else {
+            total
+                .checked_mul(entry.share_bps as i128)
+                .ok_or(Error::InvalidMilestoneAmount)?
+                / BPS_DENOMINATOR
+        };
+        remaining -= net;
+
+        // Gross amount whose payout after the fee is at least `net`
+        let amount = net
+            .checked_mul(BPS_DENOMINATOR)
+            .ok_or(Error::InvalidMilestoneAmount)?
+            .checked_add(BPS_DENOMINATOR - fee_bps - 1)
+            .ok_or(Error::InvalidMilestoneAmount)?
+            / (BPS_DENOMINATOR - fee_bps);
+
+        milestones.push_back(Milestone {
+            amount,
+            status: MilestoneStatus::Pending,
+            title: entry.title,
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        });
+    }
+    Ok(milestones)
+}
+
+fn approvals_key(escrow_id: u64, milestone_index: u32) -> (Symbol, u64, u32) {
+    (symbol_short!("approvals"), escrow_id, milestone_index)
+}
+
+fn get_approvals(env: &Env, escrow_id: u64, milestone_index: u32) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&approvals_key(escrow_id, milestone_index))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+fn set_approvals(env: &Env, escrow_id: u64, milestone_index: u32, approvals: &Vec<Address>) {
+    let key = approvals_key(escrow_id, milestone_index);
+    env.storage().persistent().set(&key, approvals);
+    env.storage().persistent().extend_ttl(&key, 100, 2_000_000);
+}
+
+/// Loads a `Threshold` escrow for an approval change, checking that
+/// `approver` is one of its signers and the milestone is still open.
+fn get_threshold_escrow(
+    env: &Env,
+    escrow_id: u64,
+    milestone_index: u32,
+    approver: &Address,
+) -> Result<Escrow, Error> {
+    let escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&get_storage_key(escrow_id))
+        .ok_or(Error::EscrowNotFound)?;
+    if escrow.approval_policy != ApprovalPolicy::Threshold || !escrow.approvers.contains(approver) {
+        return Err(Error::UnauthorizedAccess);
+    }
+
+    let milestone = escrow
+        .milestones
+        .get(milestone_index)
+        .ok_or(Error::MilestoneNotFound)?;
+    match milestone.status {
+        MilestoneStatus::Released => Err(Error::MilestoneAlreadyReleased),
+        MilestoneStatus::Refunded => Err(Error::MilestoneRefunded),
+        _ => Ok(escrow),
+    }
+}
+
+fn validate_approvers(
+    recipient: &Address,
+    approvers: &Vec<Address>,
+    threshold: u32,
+) -> Result<(), Error> {
+    if approvers.len() > MAX_APPROVERS {
+        return Err(Error::VectorTooLarge);
+    }
+    if threshold == 0 || threshold > approvers.len() {
+        return Err(Error::InvalidThreshold);
+    }
+    for (index, approver) in approvers.iter().enumerate() {
+        if approver == *recipient {
+            return Err(Error::InvalidApprover);
+        }
+        if approvers
+            .iter()
+            .take(index)
+            .any(|earlier| earlier == approver)
+        {
+            return Err(Error::InvalidApprover);
+        }
+    }
+    Ok(())
+}
+
+/// Requires the signatures the escrow's approval policy asks for when
+/// `releaser` initiates a release.
+fn authorize_release(escrow: &Escrow, releaser: Releaser) -> Result<(), Error> {
+    let approver = escrow.approver.to_option();
+    match (escrow.approval_policy, releaser) {
+        (ApprovalPolicy::DepositorOnly | ApprovalPolicy::Either, Releaser::Depositor) => {
+            escrow.depositor()?.require_auth();
+        }
+        (ApprovalPolicy::ApproverOnly | ApprovalPolicy::Either, Releaser::Approver) => {
+            approver.ok_or(Error::UnauthorizedAccess)?.require_auth();
+        }
+        (_, Releaser::Oracle) if escrow.oracle.to_option().is_some() => {}
+        (ApprovalPolicy::Threshold, Releaser::Approvals) => {}
+        (_, Releaser::Preimage) => {}
+        (ApprovalPolicy::DepositorOnly | ApprovalPolicy::Either, Releaser::Voucher) => {}
+        (ApprovalPolicy::Both, Releaser::Voucher) => {
+            approver.ok_or(Error::UnauthorizedAccess)?.require_auth();
+        }
+        (ApprovalPolicy::Both, Releaser::Depositor | Releaser::Approver) => {
+            escrow.depositor()?.require_auth();
+            approver.ok_or(Error::UnauthorizedAccess)?.require_auth();
+        }
+        _ => return Err(Error::UnauthorizedAccess),
+    }
+    Ok(())
+}
+
+/// Frees the depositor's active-escrow slot and emits `EscrowCompleted`.
+fn finish_completed_escrow(env: &Env, escrow_id: u64, depositor: &Address) {
+    release_active_slot(env, depositor);
+
+    // Standardized Event
+    env.events().publish(
+        (
+            Symbol::new(env, "Vaultix"),
+            Symbol::new(env, "EscrowCompleted"),
+            escrow_id,
+        ),
+        (),
+    );
+}
+
+/// Vested and funded amount of a stream that has not been claimed yet.
+/// Vesting stops at `paused_at` while a dispute is open.
+fn streamed_amount(env: &Env, escrow: &Escrow, schedule: &StreamSchedule) -> Result<i128, Error> {
+    let now = schedule
+        .paused_at
+        .unwrap_or_else(|| env.ledger().timestamp());
+    let vested = if now <= schedule.start_time {
+        0
+    } else if now >= schedule.end_time {
+        escrow.total_amount
+    } else {
+        let elapsed = (now - schedule.start_time) as i128;
+        let duration = (schedule.end_time - schedule.start_time) as i128;
+        escrow
+            .total_amount
+            .checked_mul(elapsed)
+            .ok_or(Error::InvalidMilestoneAmount)?
+            / duration
+    };
+
+    let claimable = vested.min(escrow.funded_amount) - escrow.total_released;
+    Ok(claimable.max(0))
+}
+
+/// Sends `amount` of a stream to the recipient, minus the fee when a
+/// treasury is configured, and records it as released.
+fn pay_streamed(
+    env: &Env,
+    escrow: &mut Escrow,
+    amount: i128,
+    config: Option<(Address, i128)>,
+) -> Result<(i128, i128), Error> {
+    if amount == 0 {
+        return Ok((0, 0));
+    }
+
+    let token_address = escrow.token_address.clone();
+    let fee = match &config {
+        Some((_, fee_bps)) => calculate_fee(amount, *fee_bps)?,
+        None => 0,
+    };
+    let payout = amount
+        .checked_sub(fee)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+
+    pay_recipients(env, escrow, &token_address, payout)?;
+    if let Some((treasury, _)) = config {
+        if fee > 0 {
+            push_funds(env, &token_address, &treasury, fee)?;
+        }
+    }
+
+    escrow.total_released = escrow
+        .total_released
+        .checked_add(amount)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+    add_token_amount(&mut escrow.released_by_token, token_address, amount)?;
+    if escrow.total_released == escrow.total_amount {
+        let mut milestone = escrow.milestones.get(0).ok_or(Error::MilestoneNotFound)?;
+        milestone.status = MilestoneStatus::Released;
+        escrow.milestones.set(0, milestone);
+    }
+
+    Ok((payout, fee))
+}
+
+fn is_depositor(escrow: &Escrow, address: &Address) -> bool {
+    escrow.depositor.to_option().as_ref() == Some(address)
+}
+
+fn is_recipient(escrow: &Escrow, address: &Address) -> bool {
+    *address == escrow.recipient
+        || escrow
+            .splits
+            .iter()
+            .any(|split| split.recipient == *address)
+        || escrow
+            .milestones
+            .iter()
+            .any(|milestone| milestone.recipient.to_option().as_ref() == Some(address))
+}
+
+/// Pays a milestone's net amount to its own recipient when it names one,
+/// otherwise to the escrow's recipients.
+fn pay_milestone(
+    env: &Env,
+    escrow: &Escrow,
+    milestone: &Milestone,
+    token_address: &Address,
+    amount: i128,
+) -> Result<(), Error> {
+    match milestone.recipient.to_option() {
+        Some(recipient) => push_funds(env, token_address, &recipient, amount),
+        None => pay_recipients(env, escrow, token_address, amount),
+    }
+}
+
+/// Pays `amount` (already net of fees) to the escrow's recipients. Each
+/// split gets its share rounded down; the last one also takes the dust.
+fn pay_recipients(
+    env: &Env,
+    escrow: &Escrow,
+    token_address: &Address,
+    amount: i128,
+) -> Result<(), Error> {
+    if escrow.splits.is_empty() {
+        return push_funds(env, token_address, &escrow.recipient, amount);
+    }
+
+    let mut remaining = amount;
+    let last_index = escrow.splits.len() - 1;
+    for (index, split) in escrow.splits.iter().enumerate() {
+        let share = if index as u32 == last_index {
+            remaining
+        } else {
+            amount
+                .checked_mul(split.share_bps as i128)
+                .ok_or(Error::InvalidMilestoneAmount)?
+                / BPS_DENOMINATOR
+        };
+        if share > 0 {
+            push_funds(env, token_address, &split.recipient, share)?;
+        }
+        remaining -= share;
+    }
+    Ok(())
+}
+
+fn validate_splits(depositor: &Address, splits: &Vec<PayoutSplit>) -> Result<(), Error> {
+    if splits.is_empty() {
+        return Err(Error::InvalidSplits);
+    }
+    if splits.len() > MAX_RECIPIENTS {
+        return Err(Error::VectorTooLarge);
+    }
+
+    let mut total_bps: u32 = 0;
+    for (index, split) in splits.iter().enumerate() {
+        if split.recipient == *depositor {
+            return Err(Error::SelfDealing);
+        }
+        if split.share_bps == 0 {
+            return Err(Error::InvalidSplits);
+        }
+        if splits
+            .iter()
+            .take(index)
+            .any(|earlier| earlier.recipient == split.recipient)
+        {
+            return Err(Error::InvalidSplits);
+        }
+        total_bps = total_bps
+            .checked_add(split.share_bps)
+            .ok_or(Error::InvalidSplits)?;
+    }
+    if total_bps as i128 != BPS_DENOMINATOR {
+        return Err(Error::InvalidSplits);
+    }
+    Ok(())
+}
+
 fn calculate_fee(amount: i128, fee_bps: i128) -> Result<i128, Error> {
     let fee_numerator = amount
         .checked_mul(fee_bps)
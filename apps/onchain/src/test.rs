@@ -1,9 +1,33 @@
 use super::*;
+use ed25519_dalek::{Signer, SigningKey};
+use proptest::prelude::*;
 use soroban_sdk::{
-    testutils::{Address as _, Events},
+    testutils::{Address as _, Events, Ledger},
     token, vec, Address, Env, IntoVal,
 };
 
+/// Stand-in for the backend condition-fulfillment service: forwards outcomes
+/// to the escrow contract, authorizing as its own contract address
+#[contract]
+struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn report(
+        env: Env,
+        escrow_contract: Address,
+        escrow_id: u64,
+        milestone_index: u32,
+        outcome: AttestationOutcome,
+    ) {
+        VaultixEscrowClient::new(&env, &escrow_contract).attest_milestone(
+            &escrow_id,
+            &milestone_index,
+            &outcome,
+        );
+    }
+}
+
 /// Helper function to create and initialize a test token
 /// Returns admin client for minting and the token address
 fn create_test_token<'a>(env: &Env, admin: &Address) -> (token::StellarAssetClient<'a>, Address) {
@@ -12,6 +36,65 @@ fn create_test_token<'a>(env: &Env, admin: &Address) -> (token::StellarAssetClie
     (token_admin_client, token_address)
 }
 
+/// Minimal token that burns 1% of every transfer, used to check that the
+/// escrow rejects tokens which deliver less than requested
+#[contract]
+struct FeeOnTransferToken;
+
+#[contractimpl]
+impl FeeOnTransferToken {
+    pub fn mint(env: Env, to: Address, amount: i128) {
+        let balance = Self::balance(env.clone(), to.clone());
+        env.storage().instance().set(&to, &(balance + amount));
+    }
+
+    pub fn balance(env: Env, id: Address) -> i128 {
+        env.storage().instance().get(&id).unwrap_or(0)
+    }
+
+    pub fn allowance(_env: Env, _from: Address, _spender: Address) -> i128 {
+        i128::MAX
+    }
+
+    pub fn decimals(_env: Env) -> u32 {
+        7
+    }
+
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        Self::move_balance(&env, from, to, amount);
+    }
+
+    pub fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+        Self::move_balance(&env, from, to, amount);
+    }
+
+    fn move_balance(env: &Env, from: Address, to: Address, amount: i128) {
+        let from_balance = Self::balance(env.clone(), from.clone());
+        let to_balance = Self::balance(env.clone(), to.clone());
+        let burned = amount / 100;
+        env.storage()
+            .instance()
+            .set(&from, &(from_balance - amount));
+        env.storage()
+            .instance()
+            .set(&to, &(to_balance + amount - burned));
+    }
+}
+
+/// Helper function to move the ledger clock
+fn set_timestamp(env: &Env, timestamp: u64) {
+    env.ledger().with_mut(|li| li.timestamp = timestamp);
+}
+
+/// Helper function to allowlist a token, registering an admin first if the
+/// test has not done so already
+fn allow_token(env: &Env, client: &VaultixEscrowClient, token_address: &Address) {
+    let _ = client.try_init(&Address::generate(env));
+    client.add_token(token_address, &0, &i128::MAX);
+}
+
 /// Helper function to create token client + admin + address
 fn create_token_contract<'a>(
     env: &Env,
@@ -22,6 +105,86 @@ fn create_token_contract<'a>(
     (token_client, token_admin, token_address)
 }
 
+fn single_milestone(env: &Env, amount: i128) -> Vec<Milestone> {
+    vec![
+        env,
+        Milestone {
+            amount,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(env, "Work"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ]
+}
+
+/// Two 1_000 milestones in the escrow token
+fn two_milestones(env: &Env) -> Vec<Milestone> {
+    let mut milestones = single_milestone(env, 1_000);
+    milestones.append(&single_milestone(env, 1_000));
+    milestones
+}
+
+/// Fee-free contract with an allowlisted token and a depositor holding the
+/// given balance, shared by the per-feature escrow setups
+struct Fixture<'a> {
+    client: VaultixEscrowClient<'a>,
+    token_client: token::Client<'a>,
+    token_address: Address,
+    depositor: Address,
+    recipient: Address,
+}
+
+fn setup_fixture<'a>(env: &Env, balance: i128) -> Fixture<'a> {
+    env.mock_all_auths();
+    set_timestamp(env, 1_700_000_000);
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(env, &contract_id);
+
+    let treasury = Address::generate(env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(env);
+    let recipient = Address::generate(env);
+    let admin = Address::generate(env);
+
+    let (token_client, token_admin, token_address) = create_token_contract(env, &admin);
+    allow_token(env, &client, &token_address);
+    token_admin.mint(&depositor, &balance);
+
+    Fixture {
+        client,
+        token_client,
+        token_address,
+        depositor,
+        recipient,
+    }
+}
+
+/// Creates and funds a plain escrow over `milestones` on a fresh fixture
+fn setup_funded_escrow<'a>(
+    env: &Env,
+    escrow_id: u64,
+    milestones: &Vec<Milestone>,
+    deadline: u64,
+) -> Fixture<'a> {
+    let total: i128 = milestones.iter().map(|milestone| milestone.amount).sum();
+    let fixture = setup_fixture(env, total);
+    fixture.client.create_and_fund_escrow(
+        &escrow_id,
+        &fixture.depositor,
+        &fixture.recipient,
+        &fixture.token_address,
+        milestones,
+        &deadline,
+    );
+    fixture
+}
+
 #[test]
 fn test_create_escrow_fails_when_paused() {
     let env = Env::default();
@@ -40,6 +203,7 @@ fn test_create_escrow_fails_when_paused() {
     let escrow_id = 1_000u64;
 
     let (_token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &10_000);
 
     let milestones = vec![
@@ -47,7 +211,12 @@ fn test_create_escrow_fails_when_paused() {
         Milestone {
             amount: 10_000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Work"),
+            title: String::from_str(&env, "Work"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -82,6 +251,7 @@ fn test_deposit_funds_fails_when_paused() {
     let escrow_id = 1_001u64;
 
     let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &10_000);
 
     let milestones = vec![
@@ -89,7 +259,12 @@ fn test_deposit_funds_fails_when_paused() {
         Milestone {
             amount: 10_000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Work"),
+            title: String::from_str(&env, "Work"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -125,6 +300,7 @@ fn test_create_and_get_escrow() {
 
     // Setup token
     let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &10000);
 
     let milestones = vec![
@@ -132,17 +308,32 @@ fn test_create_and_get_escrow() {
         Milestone {
             amount: 3000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Design"),
+            title: String::from_str(&env, "Design"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
         Milestone {
             amount: 3000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Dev"),
+            title: String::from_str(&env, "Dev"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
         Milestone {
             amount: 4000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Deploy"),
+            title: String::from_str(&env, "Deploy"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -158,7 +349,7 @@ fn test_create_and_get_escrow() {
     );
 
     let escrow = client.get_escrow(&escrow_id);
-    assert_eq!(escrow.depositor, depositor);
+    assert_eq!(escrow.depositor, OptionalAddress::Some(depositor.clone()));
     assert_eq!(escrow.recipient, recipient);
     assert_eq!(escrow.token_address, token_address);
     assert_eq!(escrow.total_amount, 10000);
@@ -214,6 +405,7 @@ fn test_deposit_funds() {
 
     // Setup token - get admin client for minting
     let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
 
     let initial_balance: i128 = 20_000;
     token_admin.mint(&depositor, &initial_balance);
@@ -223,12 +415,22 @@ fn test_deposit_funds() {
         Milestone {
             amount: 5000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Phase1"),
+            title: String::from_str(&env, "Phase1"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
         Milestone {
             amount: 5000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Phase2"),
+            title: String::from_str(&env, "Phase2"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -277,6 +479,7 @@ fn test_release_milestone_with_tokens() {
 
     // Setup token
     let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
 
     token_admin.mint(&depositor, &10_000);
 
@@ -285,12 +488,22 @@ fn test_release_milestone_with_tokens() {
         Milestone {
             amount: 6000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Phase1"),
+            title: String::from_str(&env, "Phase1"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
         Milestone {
             amount: 4000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Phase2"),
+            title: String::from_str(&env, "Phase2"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -348,6 +561,7 @@ fn test_dispute_blocks_release() {
     let escrow_id = 9u64;
 
     let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &1000);
 
     let milestones = vec![
@@ -355,7 +569,12 @@ fn test_dispute_blocks_release() {
         Milestone {
             amount: 500,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Task"),
+            title: String::from_str(&env, "Task"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -394,6 +613,7 @@ fn test_complete_escrow_with_all_releases() {
 
     // Setup token
     let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &10_000);
 
     let milestones = vec![
@@ -401,12 +621,22 @@ fn test_complete_escrow_with_all_releases() {
         Milestone {
             amount: 5000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Task1"),
+            title: String::from_str(&env, "Task1"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
         Milestone {
             amount: 5000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Task2"),
+            title: String::from_str(&env, "Task2"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -430,11 +660,93 @@ fn test_complete_escrow_with_all_releases() {
     assert_eq!(token_client.balance(&contract_id), 0);
     assert_eq!(token_client.balance(&recipient), 10_000);
 
-    client.complete_escrow(&escrow_id);
-
+    // Final confirmation completes the escrow without a separate call
     let escrow = client.get_escrow(&escrow_id);
     assert_eq!(escrow.status, EscrowStatus::Completed);
     assert_eq!(escrow.total_released, 10_000);
+
+    // complete_escrow is kept as an idempotent no-op
+    client.complete_escrow(&escrow_id);
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Completed);
+}
+
+#[test]
+fn test_last_release_auto_completes_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 21u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 6000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Phase1"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+        Milestone {
+            amount: 4000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Phase2"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Active);
+
+    client.release_milestone(&escrow_id, &1);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Completed);
+
+    let events = env.events().all();
+    let event = events.last().unwrap();
+    let expected_topics: soroban_sdk::Vec<soroban_sdk::Val> = (
+        Symbol::new(&env, "Vaultix"),
+        Symbol::new(&env, "EscrowCompleted"),
+        escrow_id,
+    )
+        .into_val(&env);
+    assert_eq!(event.1, expected_topics);
+
+    // Completed escrows cannot be released or cancelled again
+    let result = client.try_release_milestone(&escrow_id, &1);
+    assert_eq!(result, Err(Ok(Error::MilestoneAlreadyReleased)));
+    let result = client.try_cancel_escrow(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::InvalidEscrowStatus)));
 }
 
 #[test]
@@ -452,6 +764,7 @@ fn test_cancel_escrow_with_refund() {
 
     // Setup token
     let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &10_000);
 
     let milestones = vec![
@@ -459,7 +772,12 @@ fn test_cancel_escrow_with_refund() {
         Milestone {
             amount: 10000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Work"),
+            title: String::from_str(&env, "Work"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -504,13 +822,19 @@ fn test_cancel_unfunded_escrow() {
     let escrow_id = 6u64;
 
     let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
 
     let milestones = vec![
         &env,
         Milestone {
             amount: 5000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Task"),
+            title: String::from_str(&env, "Task"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -548,18 +872,29 @@ fn test_admin_resolves_dispute_to_recipient() {
     token_admin.mint(&depositor, &10000);
 
     client.init(&admin);
+    allow_token(&env, &client, &token_address);
 
     let milestones = vec![
         &env,
         Milestone {
             amount: 4000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Phase1"),
+            title: String::from_str(&env, "Phase1"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
         Milestone {
             amount: 6000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Phase2"),
+            title: String::from_str(&env, "Phase2"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -610,18 +945,29 @@ fn test_admin_resolves_dispute_to_depositor() {
     token_admin.mint(&depositor, &5000);
 
     client.init(&admin);
+    allow_token(&env, &client, &token_address);
 
     let milestones = vec![
         &env,
         Milestone {
             amount: 2000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Alpha"),
+            title: String::from_str(&env, "Alpha"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
         Milestone {
             amount: 3000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Beta"),
+            title: String::from_str(&env, "Beta"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -670,6 +1016,7 @@ fn test_duplicate_escrow_id() {
     let escrow_id = 7u64;
 
     let (_token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &10000);
 
     let milestones = vec![
@@ -677,7 +1024,12 @@ fn test_duplicate_escrow_id() {
         Milestone {
             amount: 1000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Test"),
+            title: String::from_str(&env, "Test"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -717,6 +1069,7 @@ fn test_double_release() {
     let escrow_id = 8u64;
 
     let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &2000); // Increased to cover fees
 
     let milestones = vec![
@@ -724,7 +1077,12 @@ fn test_double_release() {
         Milestone {
             amount: 1000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Task"),
+            title: String::from_str(&env, "Task"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -762,6 +1120,7 @@ fn test_too_many_milestones() {
     let escrow_id = 9u64;
 
     let (_token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &10000);
 
     let mut milestones = Vec::new(&env);
@@ -769,7 +1128,12 @@ fn test_too_many_milestones() {
         milestones.push_back(Milestone {
             amount: 100,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Task"),
+            title: String::from_str(&env, "Task"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         });
     }
 
@@ -798,6 +1162,7 @@ fn test_invalid_milestone_amount() {
     let escrow_id = 10u64;
 
     let (_token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &10000);
 
     let milestones = vec![
@@ -805,7 +1170,12 @@ fn test_invalid_milestone_amount() {
         Milestone {
             amount: 0, // Invalid: zero amount
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Task"),
+            title: String::from_str(&env, "Task"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -835,6 +1205,7 @@ fn test_unauthorized_confirm_delivery() {
     let escrow_id = 9u64;
 
     let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&buyer, &10000);
 
     let milestones = vec![
@@ -842,7 +1213,12 @@ fn test_unauthorized_confirm_delivery() {
         Milestone {
             amount: 1000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Task"),
+            title: String::from_str(&env, "Task"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -876,6 +1252,7 @@ fn test_double_confirm_delivery() {
     let escrow_id = 10u64;
 
     let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&buyer, &10000);
 
     let milestones = vec![
@@ -883,7 +1260,12 @@ fn test_double_confirm_delivery() {
         Milestone {
             amount: 1000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Task"),
+            title: String::from_str(&env, "Task"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -917,6 +1299,7 @@ fn test_zero_amount_milestone_rejected() {
     let escrow_id = 11u64;
 
     let (_token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &10000);
 
     let milestones = vec![
@@ -924,7 +1307,12 @@ fn test_zero_amount_milestone_rejected() {
         Milestone {
             amount: 0,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Test"),
+            title: String::from_str(&env, "Test"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -953,6 +1341,7 @@ fn test_negative_amount_milestone_rejected() {
     let escrow_id = 12u64;
 
     let (_token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &10000);
 
     let milestones = vec![
@@ -960,7 +1349,12 @@ fn test_negative_amount_milestone_rejected() {
         Milestone {
             amount: -1000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Test"),
+            title: String::from_str(&env, "Test"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -988,6 +1382,7 @@ fn test_self_dealing_rejected() {
     let escrow_id = 13u64;
 
     let (_token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&same_party, &10000);
 
     let milestones = vec![
@@ -995,7 +1390,12 @@ fn test_self_dealing_rejected() {
         Milestone {
             amount: 5000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Task"),
+            title: String::from_str(&env, "Task"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -1024,6 +1424,7 @@ fn test_valid_escrow_creation_succeeds() {
     let escrow_id = 14u64;
 
     let (_token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &10000);
 
     let milestones = vec![
@@ -1031,12 +1432,22 @@ fn test_valid_escrow_creation_succeeds() {
         Milestone {
             amount: 3000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Phase1"),
+            title: String::from_str(&env, "Phase1"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
         Milestone {
             amount: 7000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Phase2"),
+            title: String::from_str(&env, "Phase2"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -1052,7 +1463,7 @@ fn test_valid_escrow_creation_succeeds() {
     assert!(result.is_ok());
 
     let escrow = client.get_escrow(&escrow_id);
-    assert_eq!(escrow.depositor, depositor);
+    assert_eq!(escrow.depositor, OptionalAddress::Some(depositor.clone()));
     assert_eq!(escrow.recipient, recipient);
     assert_eq!(escrow.total_amount, 10000);
     assert_eq!(escrow.token_address, token_address);
@@ -1073,6 +1484,7 @@ fn test_double_deposit_rejected() {
     let escrow_id = 15u64;
 
     let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
 
     token_admin.mint(&depositor, &20_000);
 
@@ -1081,7 +1493,12 @@ fn test_double_deposit_rejected() {
         Milestone {
             amount: 5000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Task"),
+            title: String::from_str(&env, "Task"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -1118,6 +1535,7 @@ fn test_cancel_active_escrow_retains_fee() {
     let escrow_id = 20u64;
 
     let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
     token_admin.mint(&depositor, &10_000);
 
     let milestones = vec![
@@ -1125,7 +1543,12 @@ fn test_cancel_active_escrow_retains_fee() {
         Milestone {
             amount: 10_000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Work"),
+            title: String::from_str(&env, "Work"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -1172,13 +1595,19 @@ fn test_release_milestone_before_deposit() {
     let escrow_id = 16u64;
 
     let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
 
     let milestones = vec![
         &env,
         Milestone {
             amount: 5000,
             status: MilestoneStatus::Pending,
-            description: symbol_short!("Task"),
+            title: String::from_str(&env, "Task"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
         },
     ];
 
@@ -1195,3 +1624,3976 @@ fn test_release_milestone_before_deposit() {
     // This should panic with Error #9 (EscrowNotActive)
     client.release_milestone(&escrow_id, &0);
 }
+
+#[test]
+fn test_create_escrows_batch_with_single_funding_transfer() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let seller_a = Address::generate(&env);
+    let seller_b = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let specs = vec![
+        &env,
+        EscrowSpec {
+            escrow_id: 30,
+            recipient: seller_a.clone(),
+            token_address: token_address.clone(),
+            milestones: vec![
+                &env,
+                Milestone {
+                    amount: 2000,
+                    status: MilestoneStatus::Pending,
+                    title: String::from_str(&env, "Order1"),
+                    content_hash: OptionalHash::None,
+                    token: OptionalAddress::None,
+                    due_date: None,
+                    recipient: OptionalAddress::None,
+                    hashlock: OptionalHash::None,
+                },
+                Milestone {
+                    amount: 1000,
+                    status: MilestoneStatus::Pending,
+                    title: String::from_str(&env, "Order2"),
+                    content_hash: OptionalHash::None,
+                    token: OptionalAddress::None,
+                    due_date: None,
+                    recipient: OptionalAddress::None,
+                    hashlock: OptionalHash::None,
+                },
+            ],
+            deadline: 1706400000u64,
+        },
+        EscrowSpec {
+            escrow_id: 31,
+            recipient: seller_b.clone(),
+            token_address: token_address.clone(),
+            milestones: vec![
+                &env,
+                Milestone {
+                    amount: 4000,
+                    status: MilestoneStatus::Pending,
+                    title: String::from_str(&env, "Order3"),
+                    content_hash: OptionalHash::None,
+                    token: OptionalAddress::None,
+                    due_date: None,
+                    recipient: OptionalAddress::None,
+                    hashlock: OptionalHash::None,
+                },
+            ],
+            deadline: 1706400000u64,
+        },
+    ];
+
+    assert_eq!(client.validate_escrows_batch(&depositor, &specs), None);
+
+    token_client.approve(&depositor, &contract_id, &7000, &200);
+    assert_eq!(client.create_escrows_batch(&depositor, &specs, &true), None);
+
+    assert_eq!(token_client.balance(&contract_id), 7000);
+    assert_eq!(token_client.balance(&depositor), 3000);
+
+    let escrow_a = client.get_escrow(&30);
+    assert_eq!(escrow_a.recipient, seller_a);
+    assert_eq!(escrow_a.total_amount, 3000);
+    assert_eq!(escrow_a.status, EscrowStatus::Active);
+
+    let escrow_b = client.get_escrow(&31);
+    assert_eq!(escrow_b.recipient, seller_b);
+    assert_eq!(escrow_b.total_amount, 4000);
+    assert_eq!(escrow_b.status, EscrowStatus::Active);
+
+    // Batch-funded escrows behave like individually funded ones
+    client.release_milestone(&31, &0);
+    assert_eq!(client.get_state(&31), EscrowStatus::Completed);
+}
+
+#[test]
+fn test_create_escrows_batch_unfunded() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (token_client, _, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    let specs = vec![
+        &env,
+        EscrowSpec {
+            escrow_id: 32,
+            recipient: recipient.clone(),
+            token_address: token_address.clone(),
+            milestones: vec![
+                &env,
+                Milestone {
+                    amount: 500,
+                    status: MilestoneStatus::Pending,
+                    title: String::from_str(&env, "Task"),
+                    content_hash: OptionalHash::None,
+                    token: OptionalAddress::None,
+                    due_date: None,
+                    recipient: OptionalAddress::None,
+                    hashlock: OptionalHash::None,
+                },
+            ],
+            deadline: 1706400000u64,
+        },
+    ];
+
+    assert_eq!(
+        client.create_escrows_batch(&depositor, &specs, &false),
+        None
+    );
+
+    assert_eq!(client.get_state(&32), EscrowStatus::Created);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_create_escrows_batch_rejects_bad_spec_atomically() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+
+    let valid_spec = EscrowSpec {
+        escrow_id: 33,
+        recipient: recipient.clone(),
+        token_address: token_address.clone(),
+        milestones: vec![
+            &env,
+            Milestone {
+                amount: 1000,
+                status: MilestoneStatus::Pending,
+                title: String::from_str(&env, "Task"),
+                content_hash: OptionalHash::None,
+                token: OptionalAddress::None,
+                due_date: None,
+                recipient: OptionalAddress::None,
+                hashlock: OptionalHash::None,
+            },
+        ],
+        deadline: 1706400000u64,
+    };
+    let zero_spec = EscrowSpec {
+        escrow_id: 34,
+        recipient: recipient.clone(),
+        token_address: token_address.clone(),
+        milestones: vec![
+            &env,
+            Milestone {
+                amount: 0,
+                status: MilestoneStatus::Pending,
+                title: String::from_str(&env, "Task"),
+                content_hash: OptionalHash::None,
+                token: OptionalAddress::None,
+                due_date: None,
+                recipient: OptionalAddress::None,
+                hashlock: OptionalHash::None,
+            },
+        ],
+        deadline: 1706400000u64,
+    };
+
+    let specs = vec![&env, valid_spec.clone(), zero_spec];
+    assert_eq!(
+        client.validate_escrows_batch(&depositor, &specs),
+        Some(BatchSpecError {
+            index: 1,
+            error: Error::ZeroAmount as u32,
+        })
+    );
+
+    assert_eq!(
+        client.create_escrows_batch(&depositor, &specs, &true),
+        Some(BatchSpecError {
+            index: 1,
+            error: Error::ZeroAmount as u32,
+        })
+    );
+
+    // Nothing from the failed batch was persisted or transferred
+    assert_eq!(client.try_get_state(&33), Err(Ok(Error::EscrowNotFound)));
+    assert_eq!(token_client.balance(&contract_id), 0);
+
+    // Duplicate IDs inside one batch are reported at the second occurrence
+    let specs = vec![&env, valid_spec.clone(), valid_spec];
+    assert_eq!(
+        client.validate_escrows_batch(&depositor, &specs),
+        Some(BatchSpecError {
+            index: 1,
+            error: Error::EscrowAlreadyExists as u32,
+        })
+    );
+    assert_eq!(
+        client.create_escrows_batch(&depositor, &specs, &false),
+        Some(BatchSpecError {
+            index: 1,
+            error: Error::EscrowAlreadyExists as u32,
+        })
+    );
+    assert_eq!(client.try_get_state(&33), Err(Ok(Error::EscrowNotFound)));
+}
+
+#[test]
+fn test_create_and_fund_escrow_without_allowance() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 35u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 6000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Phase1"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+        Milestone {
+            amount: 4000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Phase2"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    // No approve: the depositor authorizes the transfer directly
+    client.create_and_fund_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+
+    assert_eq!(token_client.allowance(&depositor, &contract_id), 0);
+    assert_eq!(token_client.balance(&depositor), 0);
+    assert_eq!(token_client.balance(&contract_id), 10_000);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Active);
+    assert_eq!(escrow.total_amount, 10_000);
+
+    let result = client.try_deposit_funds(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::EscrowAlreadyFunded)));
+
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&recipient), 6000);
+}
+
+#[test]
+fn test_create_and_fund_escrow_rejects_invalid_input() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 36u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Task"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    let result = client.try_create_and_fund_escrow(
+        &escrow_id,
+        &depositor,
+        &depositor,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    assert_eq!(result, Err(Ok(Error::SelfDealing)));
+    assert_eq!(token_client.balance(&depositor), 10_000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_deposit_funds_direct_without_allowance() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 37u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 7500,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Work"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+
+    client.deposit_funds_direct(&escrow_id);
+
+    assert_eq!(token_client.allowance(&depositor, &contract_id), 0);
+    assert_eq!(token_client.balance(&depositor), 2500);
+    assert_eq!(token_client.balance(&contract_id), 7500);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Active);
+
+    let result = client.try_deposit_funds_direct(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::EscrowAlreadyFunded)));
+}
+
+#[test]
+fn test_deposit_funds_reports_insufficient_allowance() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 38u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Work"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+
+    // Allowance short by one token
+    token_client.approve(&depositor, &contract_id, &9_999, &200);
+    let result = client.try_deposit_funds(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
+
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+    assert_eq!(token_client.balance(&contract_id), 10_000);
+}
+
+#[test]
+fn test_deposit_funds_reports_insufficient_balance() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 39u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &4000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 5000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Work"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &5000, &200);
+
+    let result = client.try_deposit_funds(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
+    let result = client.try_deposit_funds_direct(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
+
+    assert_eq!(token_client.balance(&depositor), 4000);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Created);
+}
+
+#[test]
+fn test_partial_funding_releases_only_funded_milestones() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 40u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 3000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Month1"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+        Milestone {
+            amount: 3000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Month2"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+        Milestone {
+            amount: 4000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Month3"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+
+    client.deposit_partial(&escrow_id, &3000);
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::PartiallyFunded);
+    assert_eq!(escrow.funded_amount, 3000);
+    assert_eq!(token_client.balance(&contract_id), 3000);
+
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&recipient), 3000);
+
+    let result = client.try_release_milestone(&escrow_id, &1);
+    assert_eq!(result, Err(Ok(Error::MilestoneNotFunded)));
+
+    // Deposits cannot exceed the escrow total
+    let result = client.try_deposit_partial(&escrow_id, &7001);
+    assert_eq!(result, Err(Ok(Error::TotalAmountMismatch)));
+    let result = client.try_deposit_partial(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::ZeroAmount)));
+
+    // Covers milestone 1 but not milestone 2
+    client.deposit_partial(&escrow_id, &5000);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::PartiallyFunded);
+    client.release_milestone(&escrow_id, &1);
+    let result = client.try_release_milestone(&escrow_id, &2);
+    assert_eq!(result, Err(Ok(Error::MilestoneNotFunded)));
+
+    client.deposit_partial(&escrow_id, &2000);
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Active);
+    assert_eq!(escrow.funded_amount, 10_000);
+
+    client.release_milestone(&escrow_id, &2);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Completed);
+    assert_eq!(token_client.balance(&recipient), 10_000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_deposit_funds_tops_up_partial_funding() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 41u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Work"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+
+    client.deposit_partial(&escrow_id, &2500);
+    client.deposit_funds_direct(&escrow_id);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Active);
+    assert_eq!(escrow.funded_amount, 10_000);
+    assert_eq!(token_client.balance(&contract_id), 10_000);
+    assert_eq!(token_client.balance(&depositor), 0);
+}
+
+#[test]
+fn test_cancel_partially_funded_escrow_refunds_deposited_amount() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(50));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 42u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 4000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Phase1"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+        Milestone {
+            amount: 6000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Phase2"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    client.deposit_partial(&escrow_id, &4000);
+
+    client.cancel_escrow(&escrow_id);
+
+    // fee = 4_000 * 50 / 10_000 = 20, charged only on the deposited amount
+    assert_eq!(token_client.balance(&treasury), 20);
+    assert_eq!(token_client.balance(&depositor), 10_000 - 20);
+    assert_eq!(token_client.balance(&contract_id), 0);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Cancelled);
+}
+
+#[test]
+fn test_resolve_partially_funded_dispute_pays_out_deposits_only() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let escrow_id = 43u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 5000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Phase1"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+        Milestone {
+            amount: 5000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Phase2"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    client.deposit_partial(&escrow_id, &5000);
+
+    client.raise_dispute(&escrow_id, &recipient);
+    client.resolve_dispute(&escrow_id, &recipient);
+
+    assert_eq!(token_client.balance(&recipient), 5000);
+    assert_eq!(token_client.balance(&depositor), 5000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Resolved);
+    assert_eq!(escrow.total_released, 5000);
+}
+
+#[test]
+fn test_multi_token_escrow_release_and_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(100)); // 1%
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 44u64;
+
+    let (usdc_client, usdc_admin, usdc_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &usdc_address);
+    let (xlm_client, xlm_admin, xlm_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &xlm_address);
+    usdc_admin.mint(&depositor, &10_000);
+    xlm_admin.mint(&depositor, &2_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Payment"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+        Milestone {
+            amount: 2_000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Bonus"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::Some(xlm_address.clone()),
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &usdc_address,
+        &milestones,
+        &1706400000u64,
+    );
+
+    let totals = client.get_token_totals(&escrow_id);
+    assert_eq!(totals.len(), 2);
+    assert_eq!(totals.get(usdc_address.clone()), Some(10_000));
+    assert_eq!(totals.get(xlm_address.clone()), Some(2_000));
+
+    client.deposit_funds_direct(&escrow_id);
+    assert_eq!(usdc_client.balance(&contract_id), 10_000);
+    assert_eq!(xlm_client.balance(&contract_id), 2_000);
+
+    client.release_milestone(&escrow_id, &1);
+    assert_eq!(xlm_client.balance(&recipient), 1_980);
+    assert_eq!(xlm_client.balance(&treasury), 20);
+    assert_eq!(usdc_client.balance(&recipient), 0);
+
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(usdc_client.balance(&recipient), 9_900);
+    assert_eq!(usdc_client.balance(&treasury), 100);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Completed);
+    assert_eq!(escrow.released_by_token.get(usdc_address), Some(10_000));
+    assert_eq!(escrow.released_by_token.get(xlm_address), Some(2_000));
+    assert_eq!(usdc_client.balance(&contract_id), 0);
+    assert_eq!(xlm_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_multi_token_escrow_cancel_and_dispute_per_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(50));
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+
+    let (usdc_client, usdc_admin, usdc_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &usdc_address);
+    let (xlm_client, xlm_admin, xlm_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &xlm_address);
+    usdc_admin.mint(&depositor, &20_000);
+    xlm_admin.mint(&depositor, &4_000);
+    usdc_client.approve(&depositor, &contract_id, &20_000, &200);
+    xlm_client.approve(&depositor, &contract_id, &4_000, &200);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Payment"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+        Milestone {
+            amount: 2_000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Bonus"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::Some(xlm_address.clone()),
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    // Cancellation refunds each token minus its own fee
+    client.create_escrow(
+        &45u64,
+        &depositor,
+        &recipient,
+        &usdc_address,
+        &milestones,
+        &1706400000u64,
+    );
+    client.deposit_funds(&45u64);
+    client.cancel_escrow(&45u64);
+    assert_eq!(usdc_client.balance(&treasury), 50);
+    assert_eq!(xlm_client.balance(&treasury), 10);
+    assert_eq!(usdc_client.balance(&depositor), 20_000 - 50);
+    assert_eq!(xlm_client.balance(&depositor), 4_000 - 10);
+
+    // Dispute resolution returns each token's outstanding balance
+    client.create_escrow(
+        &46u64,
+        &depositor,
+        &recipient,
+        &usdc_address,
+        &milestones,
+        &1706400000u64,
+    );
+    client.deposit_funds(&46u64);
+    client.raise_dispute(&46u64, &recipient);
+    client.resolve_dispute(&46u64, &recipient);
+    assert_eq!(usdc_client.balance(&recipient), 10_000);
+    assert_eq!(xlm_client.balance(&recipient), 2_000);
+    assert_eq!(usdc_client.balance(&contract_id), 0);
+    assert_eq!(xlm_client.balance(&contract_id), 0);
+
+    // Incremental funding is limited to single-token escrows
+    client.create_escrow(
+        &47u64,
+        &depositor,
+        &recipient,
+        &usdc_address,
+        &milestones,
+        &1706400000u64,
+    );
+    let result = client.try_deposit_partial(&47u64, &1_000);
+    assert_eq!(result, Err(Ok(Error::MixedTokenPartialFunding)));
+}
+
+#[test]
+fn test_deposit_partial_rejects_milestones_in_other_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let depositor = Address::generate(&env);
+    let victim = Address::generate(&env);
+    let recipient = Address::generate(&env);
+
+    let (usdc_client, usdc_admin, usdc_address) = create_token_contract(&env, &admin);
+    let (xlm_client, xlm_admin, xlm_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &usdc_address);
+    client.add_token(&xlm_address, &0, &i128::MAX);
+    usdc_admin.mint(&depositor, &1_000);
+    xlm_admin.mint(&victim, &1_000);
+
+    // Another escrow's XLM sits in the contract
+    client.create_and_fund_escrow(
+        &101u64,
+        &victim,
+        &recipient,
+        &xlm_address,
+        &single_milestone(&env, 1_000),
+        &1706400000u64,
+    );
+
+    // Escrow token is USDC but the only milestone pays out XLM
+    let mut milestones = single_milestone(&env, 1_000);
+    let mut milestone = milestones.get(0).unwrap();
+    milestone.token = OptionalAddress::Some(xlm_address.clone());
+    milestones.set(0, milestone);
+    client.create_escrow(
+        &102u64,
+        &depositor,
+        &recipient,
+        &usdc_address,
+        &milestones,
+        &1706400000u64,
+    );
+
+    assert_eq!(
+        client.try_deposit_partial(&102u64, &1_000),
+        Err(Ok(Error::MixedTokenPartialFunding))
+    );
+    assert!(client.try_release_milestone(&102u64, &0).is_err());
+
+    assert_eq!(usdc_client.balance(&depositor), 1_000);
+    assert_eq!(xlm_client.balance(&recipient), 0);
+    assert_eq!(xlm_client.balance(&contract_id), 1_000);
+    assert!(client.check_solvency(&xlm_address));
+
+    client.cancel_escrow(&101u64);
+    assert_eq!(xlm_client.balance(&victim), 1_000);
+}
+
+#[test]
+fn test_token_allowlist_management() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let (_, usdc_address) = create_test_token(&env, &admin);
+    let (_, xlm_address) = create_test_token(&env, &admin);
+    assert_eq!(client.list_tokens().len(), 0);
+
+    client.add_token(&usdc_address, &100, &1_000_000);
+    client.add_token(&xlm_address, &0, &50_000);
+
+    let events = env.events().all();
+    let event = events.last().unwrap();
+    let expected_topics: soroban_sdk::Vec<soroban_sdk::Val> = (
+        Symbol::new(&env, "Vaultix"),
+        Symbol::new(&env, "TokenAdded"),
+        xlm_address.clone(),
+    )
+        .into_val(&env);
+    assert_eq!(event.1, expected_topics);
+
+    let tokens = client.list_tokens();
+    assert_eq!(tokens.len(), 2);
+    assert!(tokens.contains(&usdc_address));
+    assert!(tokens.contains(&xlm_address));
+    assert_eq!(
+        client.get_token_limits(&usdc_address),
+        TokenLimits {
+            min_amount: 100,
+            max_amount: 1_000_000,
+        }
+    );
+
+    // Re-adding updates the limits in place
+    client.add_token(&usdc_address, &0, &500);
+    assert_eq!(client.get_token_limits(&usdc_address).max_amount, 500);
+    assert_eq!(client.list_tokens().len(), 2);
+
+    client.remove_token(&xlm_address);
+    assert_eq!(client.list_tokens(), vec![&env, usdc_address]);
+    assert_eq!(
+        client.try_get_token_limits(&xlm_address),
+        Err(Ok(Error::TokenNotAllowed))
+    );
+    assert_eq!(
+        client.try_remove_token(&xlm_address),
+        Err(Ok(Error::TokenNotAllowed))
+    );
+
+    let result = client.try_add_token(&xlm_address, &10, &5);
+    assert_eq!(result, Err(Ok(Error::InvalidTokenLimits)));
+}
+
+#[test]
+fn test_create_escrow_rejects_unlisted_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+
+    let (_, usdc_address) = create_test_token(&env, &admin);
+    let (_, xlm_address) = create_test_token(&env, &admin);
+    client.add_token(&usdc_address, &0, &i128::MAX);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Work"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    let result = client.try_create_escrow(
+        &48u64,
+        &depositor,
+        &recipient,
+        &xlm_address,
+        &milestones,
+        &1706400000u64,
+    );
+    assert_eq!(result, Err(Ok(Error::TokenNotAllowed)));
+
+    // Milestone-level tokens must be allowlisted as well
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Bonus"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::Some(xlm_address.clone()),
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+    let result = client.try_create_escrow(
+        &48u64,
+        &depositor,
+        &recipient,
+        &usdc_address,
+        &milestones,
+        &1706400000u64,
+    );
+    assert_eq!(result, Err(Ok(Error::TokenNotAllowed)));
+
+    // Removing a token does not affect escrows that already use it
+    client.add_token(&xlm_address, &0, &i128::MAX);
+    client.create_escrow(
+        &48u64,
+        &depositor,
+        &recipient,
+        &usdc_address,
+        &milestones,
+        &1706400000u64,
+    );
+    client.remove_token(&xlm_address);
+    assert_eq!(client.get_state(&48u64), EscrowStatus::Created);
+}
+
+#[test]
+fn test_create_escrow_enforces_token_amount_limits() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    client.add_token(&token_address, &1_000, &5_000);
+
+    let escrow_with_total = |escrow_id: u64, amount: i128| {
+        let milestones = vec![
+            &env,
+            Milestone {
+                amount,
+                status: MilestoneStatus::Pending,
+                title: String::from_str(&env, "Work"),
+                content_hash: OptionalHash::None,
+                token: OptionalAddress::None,
+                due_date: None,
+                recipient: OptionalAddress::None,
+                hashlock: OptionalHash::None,
+            },
+        ];
+        client.try_create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+        )
+    };
+
+    assert_eq!(
+        escrow_with_total(49, 999),
+        Err(Ok(Error::EscrowAmountOutOfRange))
+    );
+    assert_eq!(
+        escrow_with_total(49, 5_001),
+        Err(Ok(Error::EscrowAmountOutOfRange))
+    );
+    assert!(escrow_with_total(49, 1_000).is_ok());
+    assert!(escrow_with_total(50, 5_000).is_ok());
+}
+
+#[test]
+fn test_deposit_rejects_fee_on_transfer_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let escrow_id = 51u64;
+
+    let token_address = env.register_contract(None, FeeOnTransferToken);
+    let token_client = FeeOnTransferTokenClient::new(&env, &token_address);
+    token_client.mint(&depositor, &10_000);
+    allow_token(&env, &client, &token_address);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Work"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+
+    let result = client.try_deposit_funds(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::TokenTransferFailed)));
+    let result = client.try_deposit_funds_direct(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::TokenTransferFailed)));
+
+    // The failed deposits were rolled back entirely
+    assert_eq!(token_client.balance(&depositor), 10_000);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Created);
+    assert_eq!(
+        client.get_token_holdings(&token_address),
+        TokenHoldings {
+            expected: 0,
+            actual: 0,
+        }
+    );
+}
+
+#[test]
+fn test_token_holdings_ledger_tracks_deposits_and_payouts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(100));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 52u64;
+
+    let (_token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 6000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Phase1"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+        Milestone {
+            amount: 4000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Phase2"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    client.deposit_funds_direct(&escrow_id);
+    assert_eq!(
+        client.get_token_holdings(&token_address),
+        TokenHoldings {
+            expected: 10_000,
+            actual: 10_000,
+        }
+    );
+
+    // Payout and fee both leave the ledger
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(
+        client.get_token_holdings(&token_address),
+        TokenHoldings {
+            expected: 4000,
+            actual: 4000,
+        }
+    );
+
+    // Tokens sent outside deposit_funds show up as a discrepancy
+    token_admin.mint(&contract_id, &250);
+    assert_eq!(
+        client.get_token_holdings(&token_address),
+        TokenHoldings {
+            expected: 4000,
+            actual: 4250,
+        }
+    );
+
+    client.release_milestone(&escrow_id, &1);
+    assert_eq!(
+        client.get_token_holdings(&token_address),
+        TokenHoldings {
+            expected: 0,
+            actual: 250,
+        }
+    );
+}
+
+#[test]
+fn test_solvency_check_and_surplus_sweep() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let sink = Address::generate(&env);
+    let escrow_id = 53u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Work"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    client.deposit_funds_direct(&escrow_id);
+
+    assert_eq!(client.get_liabilities(&token_address), 10_000);
+    assert!(client.check_solvency(&token_address));
+
+    // Nothing above liabilities, so nothing can be swept
+    let result = client.try_sweep_surplus(&token_address, &sink);
+    assert_eq!(result, Err(Ok(Error::NoSurplus)));
+
+    token_admin.mint(&contract_id, &700);
+    assert_eq!(client.sweep_surplus(&token_address, &sink), 700);
+    assert_eq!(token_client.balance(&sink), 700);
+    assert_eq!(token_client.balance(&contract_id), 10_000);
+    assert_eq!(client.get_liabilities(&token_address), 10_000);
+
+    // An accounting error that overstates liabilities is reported as insolvency
+    env.as_contract(&contract_id, || {
+        adjust_expected_holdings(&env, &token_address, 1).unwrap();
+    });
+    assert!(!client.check_solvency(&token_address));
+    let result = client.try_sweep_surplus(&token_address, &sink);
+    assert_eq!(result, Err(Ok(Error::NoSurplus)));
+}
+
+#[test]
+fn test_rescue_tokens_cannot_drain_escrowed_funds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let escrow_id = 54u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    let (stray_client, stray_admin, stray_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Work"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    client.deposit_funds_direct(&escrow_id);
+
+    // Escrowed funds cannot be rescued, not even a single token
+    let result = client.try_rescue_tokens(&token_address, &admin, &1);
+    assert_eq!(result, Err(Ok(Error::EscrowedFundsLocked)));
+
+    // A mistaken transfer on top of escrowed funds can be returned, but only up to the excess
+    token_admin.mint(&sender, &300);
+    token_client.transfer(&sender, &contract_id, &300);
+    let result = client.try_rescue_tokens(&token_address, &sender, &301);
+    assert_eq!(result, Err(Ok(Error::EscrowedFundsLocked)));
+    client.rescue_tokens(&token_address, &sender, &300);
+    assert_eq!(token_client.balance(&sender), 300);
+    assert_eq!(token_client.balance(&contract_id), 10_000);
+
+    let events = env.events().all();
+    let event = events.last().unwrap();
+    let expected_topics: soroban_sdk::Vec<soroban_sdk::Val> = (
+        Symbol::new(&env, "Vaultix"),
+        Symbol::new(&env, "TokensRescued"),
+        token_address.clone(),
+    )
+        .into_val(&env);
+    assert_eq!(event.1, expected_topics);
+
+    // Tokens with no escrow liabilities can be rescued in full
+    stray_admin.mint(&contract_id, &500);
+    client.rescue_tokens(&stray_address, &sender, &500);
+    assert_eq!(stray_client.balance(&sender), 500);
+
+    let result = client.try_rescue_tokens(&token_address, &sender, &0);
+    assert_eq!(result, Err(Ok(Error::ZeroAmount)));
+
+    // The escrow is still fully backed and pays out normally
+    assert!(client.check_solvency(&token_address));
+    client.raise_dispute(&escrow_id, &recipient);
+    client.resolve_dispute(&escrow_id, &recipient);
+    assert_eq!(token_client.balance(&recipient), 10_000);
+}
+
+#[test]
+fn test_reclaim_overdue_milestone() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_timestamp(&env, 1_700_000_000);
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 55u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 3000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Draft"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: Some(1_700_100_000),
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+        Milestone {
+            amount: 3000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Review"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: Some(1_700_200_000),
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+        Milestone {
+            amount: 4000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Final"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    client.deposit_funds_direct(&escrow_id);
+
+    assert_eq!(client.get_overdue_milestones(&escrow_id).len(), 0);
+    let result = client.try_reclaim_overdue_milestone(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::MilestoneNotOverdue)));
+
+    set_timestamp(&env, 1_700_150_000);
+    assert_eq!(client.get_overdue_milestones(&escrow_id), vec![&env, 0u32]);
+
+    client.reclaim_overdue_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&depositor), 3000);
+    assert_eq!(token_client.balance(&contract_id), 7000);
+    assert_eq!(client.get_overdue_milestones(&escrow_id).len(), 0);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(
+        escrow.milestones.get(0).unwrap().status,
+        MilestoneStatus::Refunded
+    );
+    assert_eq!(
+        escrow.refunded_by_token.get(token_address.clone()),
+        Some(3000)
+    );
+    assert_eq!(escrow.total_released, 0);
+
+    // A refunded milestone can neither be released nor reclaimed again
+    let result = client.try_release_milestone(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::MilestoneRefunded)));
+    let result = client.try_reclaim_overdue_milestone(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::MilestoneRefunded)));
+
+    // Milestones without a due date never become overdue
+    set_timestamp(&env, 1_800_000_000);
+    assert_eq!(client.get_overdue_milestones(&escrow_id), vec![&env, 1u32]);
+    let result = client.try_reclaim_overdue_milestone(&escrow_id, &2);
+    assert_eq!(result, Err(Ok(Error::MilestoneNotOverdue)));
+
+    // Settling the remaining milestones completes the escrow
+    client.release_milestone(&escrow_id, &2);
+    client.reclaim_overdue_milestone(&escrow_id, &1);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Completed);
+    assert_eq!(token_client.balance(&depositor), 6000);
+    assert_eq!(token_client.balance(&recipient), 4000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+    assert_eq!(client.get_liabilities(&token_address), 0);
+}
+
+#[test]
+fn test_dispute_after_reclaim_pays_only_held_funds() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_timestamp(&env, 1_700_000_000);
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let escrow_id = 56u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 2500,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Draft"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: Some(1_700_000_100),
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+        Milestone {
+            amount: 7500,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Final"),
+            content_hash: OptionalHash::None,
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    client.deposit_funds_direct(&escrow_id);
+
+    set_timestamp(&env, 1_700_000_200);
+    client.reclaim_overdue_milestone(&escrow_id, &0);
+
+    client.raise_dispute(&escrow_id, &recipient);
+    client.resolve_dispute(&escrow_id, &recipient);
+
+    assert_eq!(token_client.balance(&recipient), 7500);
+    assert_eq!(token_client.balance(&depositor), 2500);
+    assert_eq!(token_client.balance(&contract_id), 0);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.total_released, 7500);
+    assert_eq!(
+        escrow.milestones.get(0).unwrap().status,
+        MilestoneStatus::Refunded
+    );
+}
+
+#[test]
+fn test_milestone_due_date_must_fall_before_deadline() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_timestamp(&env, 1_700_000_000);
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    let create_with = |due_date: u64| {
+        let mut milestones = single_milestone(&env, 1000);
+        let mut milestone = milestones.get(0).unwrap();
+        milestone.due_date = Some(due_date);
+        milestones.set(0, milestone);
+        client.try_create_escrow(
+            &57u64,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+        )
+    };
+
+    assert_eq!(create_with(1_699_999_999), Err(Ok(Error::InvalidDeadline)));
+    assert_eq!(create_with(1_700_000_000), Err(Ok(Error::InvalidDeadline)));
+    assert_eq!(create_with(1_706_400_001), Err(Ok(Error::InvalidDeadline)));
+    assert!(create_with(1_706_400_000).is_ok());
+}
+
+#[test]
+fn test_milestone_title_and_content_hash() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 57u64;
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    let spec_hash = BytesN::from_array(&env, &[7u8; 32]);
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1000,
+            status: MilestoneStatus::Pending,
+            title: String::from_str(&env, "Logo design, 3 revisions"),
+            content_hash: OptionalHash::Some(spec_hash.clone()),
+            token: OptionalAddress::None,
+            due_date: None,
+            recipient: OptionalAddress::None,
+            hashlock: OptionalHash::None,
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+
+    let milestone = client.get_escrow(&escrow_id).milestones.get(0).unwrap();
+    assert_eq!(
+        milestone.title,
+        String::from_str(&env, "Logo design, 3 revisions")
+    );
+    assert_eq!(milestone.content_hash, OptionalHash::Some(spec_hash));
+}
+
+#[test]
+fn test_invalid_milestone_metadata_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    let create_with = |title: String, content_hash: OptionalHash| {
+        let milestones = vec![
+            &env,
+            Milestone {
+                amount: 1000,
+                status: MilestoneStatus::Pending,
+                title,
+                content_hash,
+                token: OptionalAddress::None,
+                due_date: None,
+                recipient: OptionalAddress::None,
+                hashlock: OptionalHash::None,
+            },
+        ];
+        client.try_create_escrow(
+            &58u64,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+        )
+    };
+
+    assert_eq!(
+        create_with(String::from_str(&env, ""), OptionalHash::None),
+        Err(Ok(Error::EmptyMilestoneTitle))
+    );
+    assert_eq!(
+        create_with(String::from_bytes(&env, &[b'a'; 129]), OptionalHash::None),
+        Err(Ok(Error::MilestoneTitleTooLong))
+    );
+    assert_eq!(
+        create_with(
+            String::from_str(&env, "Work"),
+            OptionalHash::Some(BytesN::from_array(&env, &[0u8; 32])),
+        ),
+        Err(Ok(Error::InvalidContentHash))
+    );
+    assert!(create_with(String::from_bytes(&env, &[b'a'; 128]), OptionalHash::None).is_ok());
+}
+
+#[test]
+fn test_set_and_get_limits() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+
+    // Setting limits requires an admin
+    let limits = Limits {
+        max_milestones: 5,
+        max_deadline_horizon: 86_400,
+        max_active_escrows: 3,
+    };
+    assert_eq!(
+        client.try_set_limits(&limits),
+        Err(Ok(Error::AdminNotInitialized))
+    );
+
+    client.init(&admin);
+
+    // Defaults are permissive
+    let defaults = client.get_limits();
+    assert_eq!(defaults.max_milestones, 20);
+    assert_eq!(defaults.max_deadline_horizon, u64::MAX);
+    assert_eq!(defaults.max_active_escrows, u32::MAX);
+
+    client.set_limits(&limits);
+    assert_eq!(client.get_limits(), limits);
+
+    let events = env.events().all();
+    let last = events.last().unwrap();
+    let topics = last.1;
+    assert_eq!(
+        topics,
+        (
+            Symbol::new(&env, "Vaultix"),
+            Symbol::new(&env, "LimitsUpdated"),
+        )
+            .into_val(&env)
+    );
+
+    // Zero or out-of-range values are rejected
+    for invalid in [
+        Limits {
+            max_milestones: 0,
+            ..limits
+        },
+        Limits {
+            max_milestones: 101,
+            ..limits
+        },
+        Limits {
+            max_deadline_horizon: 0,
+            ..limits
+        },
+        Limits {
+            max_active_escrows: 0,
+            ..limits
+        },
+    ] {
+        assert_eq!(
+            client.try_set_limits(&invalid),
+            Err(Ok(Error::InvalidLimits))
+        );
+    }
+    assert_eq!(client.get_limits(), limits);
+}
+
+#[test]
+fn test_limits_enforced_on_create() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    client.init(&admin);
+    client.add_token(&token_address, &0, &i128::MAX);
+
+    set_timestamp(&env, 1_000_000);
+    client.set_limits(&Limits {
+        max_milestones: 2,
+        max_deadline_horizon: 1_000,
+        max_active_escrows: 20,
+    });
+
+    // Milestone count above the configured maximum
+    let mut milestones = single_milestone(&env, 100);
+    milestones.append(&single_milestone(&env, 100));
+    milestones.append(&single_milestone(&env, 100));
+    assert_eq!(
+        client.try_create_escrow(
+            &59u64,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1_000_500u64,
+        ),
+        Err(Ok(Error::VectorTooLarge))
+    );
+
+    // Deadline beyond the horizon
+    assert_eq!(
+        client.try_create_escrow(
+            &59u64,
+            &depositor,
+            &recipient,
+            &token_address,
+            &single_milestone(&env, 100),
+            &1_001_001u64,
+        ),
+        Err(Ok(Error::InvalidDeadline))
+    );
+
+    // Exactly at the horizon is allowed
+    client.create_escrow(
+        &59u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &single_milestone(&env, 100),
+        &1_001_000u64,
+    );
+}
+
+#[test]
+fn test_active_escrow_cap_released_on_cancel() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    client.init(&admin);
+    client.add_token(&token_address, &0, &i128::MAX);
+    client.set_limits(&Limits {
+        max_milestones: 20,
+        max_deadline_horizon: u64::MAX,
+        max_active_escrows: 2,
+    });
+
+    for escrow_id in [60u64, 61u64] {
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &single_milestone(&env, 100),
+            &1706400000u64,
+        );
+    }
+
+    assert_eq!(
+        client.try_create_escrow(
+            &62u64,
+            &depositor,
+            &recipient,
+            &token_address,
+            &single_milestone(&env, 100),
+            &1706400000u64,
+        ),
+        Err(Ok(Error::TooManyActiveEscrows))
+    );
+
+    // Other depositors are counted separately
+    let other_depositor = Address::generate(&env);
+    client.create_escrow(
+        &63u64,
+        &other_depositor,
+        &recipient,
+        &token_address,
+        &single_milestone(&env, 100),
+        &1706400000u64,
+    );
+
+    // Cancelling frees a slot
+    client.cancel_escrow(&60u64);
+    client.create_escrow(
+        &62u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &single_milestone(&env, 100),
+        &1706400000u64,
+    );
+}
+
+#[test]
+fn test_create_escrow_rejects_empty_milestones() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    let result = client.try_create_escrow(
+        &64u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &Vec::new(&env),
+        &1706400000u64,
+    );
+    assert_eq!(result, Err(Ok(Error::NoMilestones)));
+}
+
+#[test]
+fn test_create_escrow_rejects_past_deadline() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_timestamp(&env, 1_700_000_000);
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    // A deadline at or before the current ledger time is already expired
+    for deadline in [1_600_000_000u64, 1_700_000_000u64] {
+        let result = client.try_create_escrow(
+            &65u64,
+            &depositor,
+            &recipient,
+            &token_address,
+            &single_milestone(&env, 100),
+            &deadline,
+        );
+        assert_eq!(result, Err(Ok(Error::InvalidDeadline)));
+    }
+
+    client.create_escrow(
+        &65u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &single_milestone(&env, 100),
+        &1_700_000_001u64,
+    );
+}
+
+#[test]
+fn test_create_escrow_rejects_non_token_contract() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+
+    // Allowlisted by mistake: the address hosts no token contract
+    let bogus_token = Address::generate(&env);
+    allow_token(&env, &client, &bogus_token);
+
+    let result = client.try_create_escrow(
+        &66u64,
+        &depositor,
+        &recipient,
+        &bogus_token,
+        &single_milestone(&env, 100),
+        &1706400000u64,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidToken)));
+}
+
+fn milestones_from_amounts(env: &Env, amounts: &[i128]) -> Vec<Milestone> {
+    let mut milestones = Vec::new(env);
+    for amount in amounts {
+        milestones.append(&single_milestone(env, *amount));
+    }
+    milestones
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn prop_validate_milestones_total_is_positive_sum(
+        amounts in prop::collection::vec(any::<i128>(), 0..=24),
+    ) {
+        let env = Env::default();
+        let milestones = milestones_from_amounts(&env, &amounts);
+
+        match validate_milestones(&milestones, DEFAULT_MAX_MILESTONES) {
+            Ok(total) => {
+                // Accepted escrows are never empty, zero-valued or overflowing
+                prop_assert!(!amounts.is_empty());
+                prop_assert!(amounts.len() <= DEFAULT_MAX_MILESTONES as usize);
+                prop_assert!(amounts.iter().all(|amount| *amount > 0));
+                prop_assert!(total > 0);
+                let mut expected: i128 = 0;
+                for amount in &amounts {
+                    expected = expected.checked_add(*amount).unwrap();
+                }
+                prop_assert_eq!(total, expected);
+            }
+            Err(error) => {
+                let overflows = amounts
+                    .iter()
+                    .try_fold(0i128, |total, amount| total.checked_add(*amount))
+                    .is_none();
+                prop_assert!(
+                    amounts.is_empty()
+                        || amounts.len() > DEFAULT_MAX_MILESTONES as usize
+                        || amounts.iter().any(|amount| *amount <= 0)
+                        || overflows,
+                    "unexpected rejection: {:?}",
+                    error
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn prop_validate_milestones_rejects_any_non_positive_amount(
+        mut amounts in prop::collection::vec(1i128..=i128::MAX / 32, 1..=20),
+        bad_amount in i128::MIN..=0,
+        index in any::<prop::sample::Index>(),
+    ) {
+        let env = Env::default();
+        let bad_index = index.index(amounts.len());
+        amounts[bad_index] = bad_amount;
+        let milestones = milestones_from_amounts(&env, &amounts);
+
+        prop_assert_eq!(
+            validate_milestones(&milestones, DEFAULT_MAX_MILESTONES),
+            Err(Error::ZeroAmount)
+        );
+    }
+
+    #[test]
+    fn prop_calculate_fee_bounded_by_amount(
+        amount in 0i128..=i128::MAX,
+        fee_bps in 0i128..=BPS_DENOMINATOR,
+    ) {
+        match calculate_fee(amount, fee_bps) {
+            Ok(fee) => {
+                prop_assert!(fee >= 0);
+                prop_assert!(fee <= amount);
+                prop_assert!(amount - fee >= 0);
+            }
+            // Only products that do not fit in i128 may be refused
+            Err(error) => {
+                prop_assert_eq!(error, Error::InvalidMilestoneAmount);
+                prop_assert!(amount.checked_mul(fee_bps).is_none());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_subscription_unlocks_one_period_per_interval() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_timestamp(&env, 1_700_000_000);
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(100)); // 1%
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let subscription_id = 1u64;
+    let month = 2_592_000u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &50_000);
+
+    client.create_subscription(
+        &subscription_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &10_000,
+        &month,
+        &3,
+    );
+    assert_eq!(token_client.balance(&depositor), 20_000);
+    assert_eq!(token_client.balance(&contract_id), 30_000);
+    assert_eq!(client.get_claimable_periods(&subscription_id), 0);
+    assert_eq!(
+        client.try_claim_subscription(&subscription_id),
+        Err(Ok(Error::NothingToClaim))
+    );
+
+    // Two intervals later two periods are claimable in one call
+    set_timestamp(&env, 1_700_000_000 + 2 * month + 5);
+    assert_eq!(client.get_claimable_periods(&subscription_id), 2);
+    assert_eq!(client.claim_subscription(&subscription_id), 19_800);
+    assert_eq!(token_client.balance(&recipient), 19_800);
+    assert_eq!(token_client.balance(&treasury), 200);
+
+    // Top up two more periods; unlocking never runs past what is funded
+    client.top_up_subscription(&subscription_id, &2);
+    assert_eq!(token_client.balance(&depositor), 0);
+    set_timestamp(&env, 1_700_000_000 + 10 * month);
+    assert_eq!(client.get_claimable_periods(&subscription_id), 3);
+    client.claim_subscription(&subscription_id);
+
+    let subscription = client.get_subscription(&subscription_id);
+    assert_eq!(subscription.funded_periods, 5);
+    assert_eq!(subscription.claimed_periods, 5);
+    assert_eq!(subscription.status, SubscriptionStatus::Active);
+    assert_eq!(token_client.balance(&recipient), 49_500);
+    assert_eq!(token_client.balance(&contract_id), 0);
+    assert_eq!(client.get_token_holdings(&token_address).expected, 0);
+}
+
+#[test]
+fn test_top_up_of_lapsed_subscription_unlocks_from_now() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_timestamp(&env, 1_700_000_000);
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let subscription_id = 3u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &6_000);
+
+    client.create_subscription(
+        &subscription_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &1_000,
+        &10,
+        &1,
+    );
+
+    // Long after the only funded period, the top-up does not pay for the gap
+    set_timestamp(&env, 1_700_001_000);
+    client.top_up_subscription(&subscription_id, &5);
+    assert_eq!(client.get_claimable_periods(&subscription_id), 1);
+
+    set_timestamp(&env, 1_700_001_010);
+    assert_eq!(client.get_claimable_periods(&subscription_id), 2);
+    set_timestamp(&env, 1_700_001_035);
+    assert_eq!(client.get_claimable_periods(&subscription_id), 4);
+    set_timestamp(&env, 1_700_002_000);
+    assert_eq!(client.get_claimable_periods(&subscription_id), 6);
+
+    client.claim_subscription(&subscription_id);
+    assert_eq!(token_client.balance(&recipient), 6_000);
+}
+
+#[test]
+fn test_top_up_subscription_respects_token_limits() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_timestamp(&env, 1_700_000_000);
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let subscription_id = 4u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    client.add_token(&token_address, &0, &5_000);
+    token_admin.mint(&depositor, &10_000);
+
+    client.create_subscription(
+        &subscription_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &1_000,
+        &10,
+        &3,
+    );
+
+    // Funding past the token's maximum is refused just like at creation
+    assert_eq!(
+        client.try_top_up_subscription(&subscription_id, &3),
+        Err(Ok(Error::EscrowAmountOutOfRange))
+    );
+    client.top_up_subscription(&subscription_id, &2);
+    assert_eq!(client.get_subscription(&subscription_id).funded_periods, 5);
+    assert_eq!(token_client.balance(&contract_id), 5_000);
+}
+
+#[test]
+fn test_stop_subscription_refunds_unused_periods() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_timestamp(&env, 1_700_000_000);
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let subscription_id = 2u64;
+    let week = 604_800u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &4_000);
+
+    client.create_subscription(
+        &subscription_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &1_000,
+        &week,
+        &4,
+    );
+
+    // One period has unlocked; the remaining three go back to the depositor
+    set_timestamp(&env, 1_700_000_000 + week + week / 2);
+    assert_eq!(client.stop_subscription(&subscription_id), 3_000);
+    assert_eq!(token_client.balance(&depositor), 3_000);
+
+    let subscription = client.get_subscription(&subscription_id);
+    assert_eq!(subscription.status, SubscriptionStatus::Stopped);
+    assert_eq!(subscription.funded_periods, 1);
+
+    assert_eq!(
+        client.try_top_up_subscription(&subscription_id, &1),
+        Err(Ok(Error::EscrowNotActive))
+    );
+    assert_eq!(
+        client.try_stop_subscription(&subscription_id),
+        Err(Ok(Error::EscrowNotActive))
+    );
+
+    // The unlocked period is still paid out, even long after the stop
+    set_timestamp(&env, 1_700_000_000 + 10 * week);
+    assert_eq!(client.get_claimable_periods(&subscription_id), 1);
+    client.claim_subscription(&subscription_id);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+    assert_eq!(
+        client.get_subscription(&subscription_id).status,
+        SubscriptionStatus::Completed
+    );
+}
+
+#[test]
+fn test_create_subscription_validation() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    assert_eq!(
+        client.try_create_subscription(
+            &3u64,
+            &depositor,
+            &recipient,
+            &token_address,
+            &1_000,
+            &3_600,
+            &2
+        ),
+        Err(Ok(Error::TokenNotAllowed))
+    );
+    allow_token(&env, &client, &token_address);
+
+    assert_eq!(
+        client.try_create_subscription(
+            &3u64,
+            &depositor,
+            &depositor,
+            &token_address,
+            &1_000,
+            &3_600,
+            &2
+        ),
+        Err(Ok(Error::SelfDealing))
+    );
+    assert_eq!(
+        client.try_create_subscription(
+            &3u64,
+            &depositor,
+            &recipient,
+            &token_address,
+            &1_000,
+            &3_600,
+            &0
+        ),
+        Err(Ok(Error::ZeroAmount))
+    );
+    assert_eq!(
+        client.try_create_subscription(
+            &3u64,
+            &depositor,
+            &recipient,
+            &token_address,
+            &1_000,
+            &0,
+            &2
+        ),
+        Err(Ok(Error::InvalidInterval))
+    );
+    assert_eq!(
+        client.try_create_subscription(
+            &3u64,
+            &depositor,
+            &recipient,
+            &token_address,
+            &10_000,
+            &3_600,
+            &2
+        ),
+        Err(Ok(Error::InsufficientBalance))
+    );
+    assert_eq!(
+        client.try_get_claimable_periods(&3u64),
+        Err(Ok(Error::SubscriptionNotFound))
+    );
+}
+
+/// Creates and funds a 10_000 stream vesting over 1_000 seconds from
+/// `1_700_001_000`, with a zero-fee treasury and an admin for disputes
+fn setup_stream<'a>(
+    env: &Env,
+    escrow_id: u64,
+) -> (VaultixEscrowClient<'a>, token::Client<'a>, Address, Address) {
+    let fixture = setup_fixture(env, 10_000);
+    fixture.client.create_stream_escrow(
+        &escrow_id,
+        &fixture.depositor,
+        &fixture.recipient,
+        &fixture.token_address,
+        &10_000,
+        &1_700_001_000,
+        &1_700_002_000,
+    );
+    fixture.client.deposit_funds_direct(&escrow_id);
+
+    (
+        fixture.client,
+        fixture.token_client,
+        fixture.depositor,
+        fixture.recipient,
+    )
+}
+
+#[test]
+fn test_stream_vests_linearly() {
+    let env = Env::default();
+    let escrow_id = 67u64;
+    let (client, token_client, _, recipient) = setup_stream(&env, escrow_id);
+
+    // Nothing vests before the start
+    assert_eq!(client.get_streamed_amount(&escrow_id), 0);
+    assert_eq!(
+        client.try_claim_streamed(&escrow_id),
+        Err(Ok(Error::NothingToClaim))
+    );
+
+    // Milestone releases do not apply to streams
+    assert_eq!(
+        client.try_release_milestone(&escrow_id, &0),
+        Err(Ok(Error::WrongReleaseMode))
+    );
+
+    set_timestamp(&env, 1_700_001_250);
+    assert_eq!(client.get_streamed_amount(&escrow_id), 2_500);
+    assert_eq!(client.claim_streamed(&escrow_id), 2_500);
+    assert_eq!(token_client.balance(&recipient), 2_500);
+    assert_eq!(client.get_streamed_amount(&escrow_id), 0);
+
+    // Past the end everything left is claimable and the escrow completes
+    set_timestamp(&env, 1_700_005_000);
+    assert_eq!(client.claim_streamed(&escrow_id), 7_500);
+    assert_eq!(token_client.balance(&recipient), 10_000);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Completed);
+    assert_eq!(escrow.total_released, 10_000);
+    assert_eq!(
+        escrow.milestones.get(0).unwrap().status,
+        MilestoneStatus::Released
+    );
+}
+
+#[test]
+fn test_stream_pauses_during_dispute() {
+    let env = Env::default();
+    let escrow_id = 68u64;
+    let (client, token_client, depositor, recipient) = setup_stream(&env, escrow_id);
+
+    set_timestamp(&env, 1_700_001_250);
+    client.claim_streamed(&escrow_id);
+
+    // Vesting freezes at the moment the dispute is raised
+    set_timestamp(&env, 1_700_001_500);
+    client.raise_dispute(&escrow_id, &depositor);
+    set_timestamp(&env, 1_700_003_000);
+    assert_eq!(client.get_streamed_amount(&escrow_id), 2_500);
+    assert_eq!(
+        client.try_claim_streamed(&escrow_id),
+        Err(Ok(Error::EscrowNotActive))
+    );
+
+    // The resolution decides everything still held
+    client.resolve_dispute(&escrow_id, &depositor);
+    assert_eq!(token_client.balance(&recipient), 2_500);
+    assert_eq!(token_client.balance(&depositor), 7_500);
+}
+
+#[test]
+fn test_cancel_stream_splits_vested_and_unvested() {
+    let env = Env::default();
+    let escrow_id = 69u64;
+    let (client, token_client, depositor, recipient) = setup_stream(&env, escrow_id);
+
+    set_timestamp(&env, 1_700_001_100);
+    client.claim_streamed(&escrow_id);
+
+    set_timestamp(&env, 1_700_001_300);
+    client.cancel_escrow(&escrow_id);
+
+    assert_eq!(token_client.balance(&recipient), 3_000);
+    assert_eq!(token_client.balance(&depositor), 7_000);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Cancelled);
+}
+
+#[test]
+fn test_create_stream_rejects_invalid_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    let result = client.try_create_stream_escrow(
+        &70u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &10_000,
+        &1_700_002_000,
+        &1_700_002_000,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidStreamWindow)));
+}
+
+fn split(recipient: &Address, share_bps: u32) -> PayoutSplit {
+    PayoutSplit {
+        recipient: recipient.clone(),
+        share_bps,
+    }
+}
+
+#[test]
+fn test_split_escrow_divides_payouts_after_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(100)); // 1%
+
+    let depositor = Address::generate(&env);
+    let lead = Address::generate(&env);
+    let designer = Address::generate(&env);
+    let writer = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 71u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &10_100);
+
+    let mut milestones = single_milestone(&env, 10_000);
+    milestones.append(&single_milestone(&env, 100));
+    let splits = vec![
+        &env,
+        split(&lead, 5_000),
+        split(&designer, 3_000),
+        split(&writer, 2_000),
+    ];
+    client.create_split_escrow(
+        &escrow_id,
+        &depositor,
+        &splits,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    client.deposit_funds_direct(&escrow_id);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.recipient, lead);
+    assert_eq!(escrow.splits, splits);
+
+    // 10_000 minus the 1% fee leaves 9_900 to split
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&treasury), 100);
+    assert_eq!(token_client.balance(&lead), 4_950);
+    assert_eq!(token_client.balance(&designer), 2_970);
+    assert_eq!(token_client.balance(&writer), 1_980);
+
+    // Rounding dust goes to the last recipient: 99 -> 49 / 29 / 21
+    client.release_milestone(&escrow_id, &1);
+    assert_eq!(token_client.balance(&lead), 4_999);
+    assert_eq!(token_client.balance(&designer), 2_999);
+    assert_eq!(token_client.balance(&writer), 2_001);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_split_recipient_can_raise_dispute() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let lead = Address::generate(&env);
+    let designer = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let escrow_id = 72u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    client.add_token(&token_address, &0, &i128::MAX);
+    token_admin.mint(&depositor, &1_000);
+
+    client.create_split_escrow(
+        &escrow_id,
+        &depositor,
+        &vec![&env, split(&lead, 6_000), split(&designer, 4_000)],
+        &token_address,
+        &single_milestone(&env, 1_000),
+        &1706400000u64,
+    );
+    client.deposit_funds_direct(&escrow_id);
+
+    assert_eq!(
+        client.try_raise_dispute(&escrow_id, &outsider),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+    client.raise_dispute(&escrow_id, &designer);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Disputed);
+
+    // Ruling for any recipient pays the recipients side by share
+    client.resolve_dispute(&escrow_id, &designer);
+    assert_eq!(token_client.balance(&lead), 600);
+    assert_eq!(token_client.balance(&designer), 400);
+}
+
+#[test]
+fn test_create_split_escrow_validates_shares() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    let create_with = |splits: Vec<PayoutSplit>| {
+        client.try_create_split_escrow(
+            &73u64,
+            &depositor,
+            &splits,
+            &token_address,
+            &single_milestone(&env, 1_000),
+            &1706400000u64,
+        )
+    };
+
+    assert_eq!(create_with(Vec::new(&env)), Err(Ok(Error::InvalidSplits)));
+    assert_eq!(
+        create_with(vec![&env, split(&first, 5_000), split(&second, 4_999)]),
+        Err(Ok(Error::InvalidSplits))
+    );
+    assert_eq!(
+        create_with(vec![&env, split(&first, 10_000), split(&second, 0)]),
+        Err(Ok(Error::InvalidSplits))
+    );
+    assert_eq!(
+        create_with(vec![&env, split(&first, 5_000), split(&first, 5_000)]),
+        Err(Ok(Error::InvalidSplits))
+    );
+    assert_eq!(
+        create_with(vec![&env, split(&first, 5_000), split(&depositor, 5_000)]),
+        Err(Ok(Error::SelfDealing))
+    );
+    assert!(create_with(vec![&env, split(&first, 5_000), split(&second, 5_000)]).is_ok());
+}
+
+fn milestone_for(env: &Env, amount: i128, recipient: &Address) -> Milestone {
+    Milestone {
+        amount,
+        status: MilestoneStatus::Pending,
+        title: String::from_str(env, "Deliverable"),
+        content_hash: OptionalHash::None,
+        token: OptionalAddress::None,
+        due_date: None,
+        recipient: OptionalAddress::Some(recipient.clone()),
+        hashlock: OptionalHash::None,
+    }
+}
+
+#[test]
+fn test_milestone_recipient_overrides_escrow_recipient() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(100)); // 1%
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let plumber = Address::generate(&env);
+    let electrician = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 74u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &6_000);
+
+    let mut milestones = vec![
+        &env,
+        milestone_for(&env, 2_000, &plumber),
+        milestone_for(&env, 3_000, &electrician),
+    ];
+    milestones.append(&single_milestone(&env, 1_000));
+    client.create_and_fund_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&plumber), 1_980);
+
+    client.confirm_delivery(&escrow_id, &1, &depositor);
+    assert_eq!(token_client.balance(&electrician), 3_000);
+
+    // Milestones without their own recipient still pay the escrow recipient
+    client.release_milestone(&escrow_id, &2);
+    assert_eq!(token_client.balance(&recipient), 990);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Completed);
+}
+
+#[test]
+fn test_dispute_resolution_pays_milestone_recipients() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let subcontractor = Address::generate(&env);
+    let escrow_id = 75u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    client.add_token(&token_address, &0, &i128::MAX);
+    token_admin.mint(&depositor, &3_000);
+
+    let mut milestones = vec![&env, milestone_for(&env, 2_000, &subcontractor)];
+    milestones.append(&single_milestone(&env, 1_000));
+    client.create_and_fund_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+
+    // The subcontractor is a recipient of this escrow and may dispute it
+    client.raise_dispute(&escrow_id, &subcontractor);
+    client.resolve_dispute(&escrow_id, &recipient);
+
+    assert_eq!(token_client.balance(&subcontractor), 2_000);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_milestone_recipient_cannot_be_depositor() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    let result = client.try_create_escrow(
+        &76u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &vec![&env, milestone_for(&env, 1_000, &depositor)],
+        &1706400000u64,
+    );
+    assert_eq!(result, Err(Ok(Error::SelfDealing)));
+}
+
+/// Creates a funded two-milestone escrow governed by `policy`, with fees off
+fn setup_approver_escrow<'a>(
+    env: &Env,
+    escrow_id: u64,
+    policy: ApprovalPolicy,
+) -> (VaultixEscrowClient<'a>, Address, Address, Address) {
+    let fixture = setup_fixture(env, 2_000);
+    let inspector = Address::generate(env);
+    fixture.client.create_escrow_with_approver(
+        &escrow_id,
+        &fixture.depositor,
+        &fixture.recipient,
+        &fixture.token_address,
+        &two_milestones(env),
+        &1706400000u64,
+        &inspector,
+        &policy,
+    );
+    fixture.client.deposit_funds_direct(&escrow_id);
+
+    (
+        fixture.client,
+        fixture.depositor,
+        fixture.recipient,
+        inspector,
+    )
+}
+
+fn signed_by(env: &Env, address: &Address) -> bool {
+    env.auths().iter().any(|(signer, _)| signer == address)
+}
+
+#[test]
+fn test_approver_only_policy() {
+    let env = Env::default();
+    let escrow_id = 77u64;
+    let (client, depositor, _, inspector) =
+        setup_approver_escrow(&env, escrow_id, ApprovalPolicy::ApproverOnly);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.approver, OptionalAddress::Some(inspector.clone()));
+    assert_eq!(escrow.approval_policy, ApprovalPolicy::ApproverOnly);
+
+    // The depositor can no longer release on their own
+    assert_eq!(
+        client.try_release_milestone(&escrow_id, &0),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+    assert_eq!(
+        client.try_confirm_delivery(&escrow_id, &0, &depositor),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+
+    client.release_milestone_by_approver(&escrow_id, &0);
+    assert!(signed_by(&env, &inspector));
+    assert!(!signed_by(&env, &depositor));
+}
+
+#[test]
+fn test_either_policy_accepts_depositor_or_approver() {
+    let env = Env::default();
+    let escrow_id = 78u64;
+    let (client, depositor, _, inspector) =
+        setup_approver_escrow(&env, escrow_id, ApprovalPolicy::Either);
+
+    client.release_milestone(&escrow_id, &0);
+    assert!(signed_by(&env, &depositor));
+    assert!(!signed_by(&env, &inspector));
+
+    client.release_milestone_by_approver(&escrow_id, &1);
+    assert!(signed_by(&env, &inspector));
+    assert!(!signed_by(&env, &depositor));
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Completed);
+}
+
+#[test]
+fn test_both_policy_requires_both_signatures() {
+    let env = Env::default();
+    let escrow_id = 79u64;
+    let (client, depositor, _, inspector) =
+        setup_approver_escrow(&env, escrow_id, ApprovalPolicy::Both);
+
+    client.release_milestone(&escrow_id, &0);
+    assert!(signed_by(&env, &depositor));
+    assert!(signed_by(&env, &inspector));
+
+    client.confirm_delivery(&escrow_id, &1, &depositor);
+    assert!(signed_by(&env, &depositor));
+    assert!(signed_by(&env, &inspector));
+}
+
+#[test]
+fn test_depositor_only_policy_rejects_approver() {
+    let env = Env::default();
+    let escrow_id = 80u64;
+    let (client, _, _, _) = setup_approver_escrow(&env, escrow_id, ApprovalPolicy::DepositorOnly);
+
+    assert_eq!(
+        client.try_release_milestone_by_approver(&escrow_id, &0),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+
+    // Plain escrows have no approver at all
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.approval_policy, ApprovalPolicy::DepositorOnly);
+}
+
+#[test]
+fn test_approver_must_be_third_party() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    for approver in [&depositor, &recipient] {
+        let result = client.try_create_escrow_with_approver(
+            &81u64,
+            &depositor,
+            &recipient,
+            &token_address,
+            &single_milestone(&env, 1_000),
+            &1706400000u64,
+            approver,
+            &ApprovalPolicy::Either,
+        );
+        assert_eq!(result, Err(Ok(Error::InvalidApprover)));
+    }
+}
+
+#[test]
+fn test_approver_escrow_rejects_threshold_policy() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let approver = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    let result = client.try_create_escrow_with_approver(
+        &81u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &single_milestone(&env, 1_000),
+        &1706400000u64,
+        &approver,
+        &ApprovalPolicy::Threshold,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidThreshold)));
+    assert_eq!(client.try_get_state(&81u64), Err(Ok(Error::EscrowNotFound)));
+}
+
+#[test]
+fn test_multisig_release_executes_at_threshold() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let cfo = Address::generate(&env);
+    let controller = Address::generate(&env);
+    let manager = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 82u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &5_000);
+
+    client.create_multisig_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &single_milestone(&env, 5_000),
+        &1706400000u64,
+        &vec![&env, cfo.clone(), controller.clone(), manager.clone()],
+        &2,
+    );
+    client.deposit_funds_direct(&escrow_id);
+
+    // No single signer can release, not even the depositor
+    assert_eq!(
+        client.try_release_milestone(&escrow_id, &0),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+    assert_eq!(
+        client.try_approve_milestone(&escrow_id, &0, &outsider),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+
+    client.approve_milestone(&escrow_id, &0, &cfo);
+    assert_eq!(
+        client.try_approve_milestone(&escrow_id, &0, &cfo),
+        Err(Ok(Error::AlreadyApproved))
+    );
+    assert_eq!(
+        client.get_approvals(&escrow_id, &0),
+        vec![&env, cfo.clone()]
+    );
+    assert_eq!(token_client.balance(&recipient), 0);
+
+    // Approvals can be withdrawn before the release happens
+    client.revoke_approval(&escrow_id, &0, &cfo);
+    assert_eq!(client.get_approvals(&escrow_id, &0).len(), 0);
+    assert_eq!(
+        client.try_revoke_approval(&escrow_id, &0, &cfo),
+        Err(Ok(Error::ApprovalNotFound))
+    );
+
+    client.approve_milestone(&escrow_id, &0, &controller);
+    client.approve_milestone(&escrow_id, &0, &manager);
+    assert_eq!(token_client.balance(&recipient), 5_000);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Completed);
+    assert_eq!(client.get_approvals(&escrow_id, &0).len(), 0);
+
+    assert_eq!(
+        client.try_approve_milestone(&escrow_id, &0, &cfo),
+        Err(Ok(Error::MilestoneAlreadyReleased))
+    );
+    assert_eq!(
+        client.try_revoke_approval(&escrow_id, &0, &manager),
+        Err(Ok(Error::MilestoneAlreadyReleased))
+    );
+}
+
+#[test]
+fn test_create_multisig_escrow_validates_approvers() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    let create_with = |approvers: Vec<Address>, threshold: u32| {
+        client.try_create_multisig_escrow(
+            &83u64,
+            &depositor,
+            &recipient,
+            &token_address,
+            &single_milestone(&env, 1_000),
+            &1706400000u64,
+            &approvers,
+            &threshold,
+        )
+    };
+
+    let approvers = vec![&env, first.clone(), second.clone()];
+    assert_eq!(
+        create_with(approvers.clone(), 0),
+        Err(Ok(Error::InvalidThreshold))
+    );
+    assert_eq!(
+        create_with(approvers.clone(), 3),
+        Err(Ok(Error::InvalidThreshold))
+    );
+    assert_eq!(
+        create_with(vec![&env, first.clone(), recipient.clone()], 1),
+        Err(Ok(Error::InvalidApprover))
+    );
+    assert_eq!(
+        create_with(vec![&env, first.clone(), first.clone()], 1),
+        Err(Ok(Error::InvalidApprover))
+    );
+
+    // The depositor may be one of the signers
+    assert!(create_with(vec![&env, first, depositor.clone()], 2).is_ok());
+}
+
+/// Creates a funded two-milestone escrow attested by a `MockOracle`, then
+/// turns auth mocking off so only real authorization passes
+fn setup_oracle_escrow<'a>(
+    env: &Env,
+    escrow_id: u64,
+) -> (
+    VaultixEscrowClient<'a>,
+    MockOracleClient<'a>,
+    token::Client<'a>,
+    Address,
+) {
+    let fixture = setup_fixture(env, 2_000);
+    let oracle_id = env.register_contract(None, MockOracle);
+    fixture.client.create_oracle_escrow(
+        &escrow_id,
+        &fixture.depositor,
+        &fixture.recipient,
+        &fixture.token_address,
+        &two_milestones(env),
+        &1706400000u64,
+        &oracle_id,
+    );
+    fixture.client.deposit_funds_direct(&escrow_id);
+    env.set_auths(&[]);
+
+    (
+        fixture.client,
+        MockOracleClient::new(env, &oracle_id),
+        fixture.token_client,
+        fixture.recipient,
+    )
+}
+
+#[test]
+fn test_oracle_attestation_releases_milestone() {
+    let env = Env::default();
+    let escrow_id = 84u64;
+    let (client, oracle, token_client, recipient) = setup_oracle_escrow(&env, escrow_id);
+
+    oracle.report(
+        &client.address,
+        &escrow_id,
+        &0,
+        &AttestationOutcome::Fulfilled,
+    );
+    assert_eq!(token_client.balance(&recipient), 1_000);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(
+        escrow.milestones.get(0).unwrap().status,
+        MilestoneStatus::Released
+    );
+    assert_eq!(
+        escrow.milestones.get(1).unwrap().status,
+        MilestoneStatus::Pending
+    );
+}
+
+#[test]
+fn test_oracle_failure_opens_dispute() {
+    let env = Env::default();
+    let escrow_id = 85u64;
+    let (client, oracle, token_client, recipient) = setup_oracle_escrow(&env, escrow_id);
+
+    oracle.report(&client.address, &escrow_id, &1, &AttestationOutcome::Failed);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Disputed);
+    assert_eq!(token_client.balance(&recipient), 0);
+
+    // A disputed escrow cannot be released by a later attestation
+    env.mock_all_auths();
+    assert_eq!(
+        client.try_attest_milestone(&escrow_id, &0, &AttestationOutcome::Fulfilled),
+        Err(Ok(Error::EscrowNotActive))
+    );
+}
+
+#[test]
+fn test_attest_requires_configured_oracle() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    client.create_escrow(
+        &86u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &single_milestone(&env, 1_000),
+        &1706400000u64,
+    );
+
+    let result = client.try_attest_milestone(&86u64, &0, &AttestationOutcome::Fulfilled);
+    assert_eq!(result, Err(Ok(Error::UnauthorizedAccess)));
+}
+
+/// Creates a funded escrow whose only milestone is locked by the hash of
+/// `preimage`, with the escrow deadline as its timelock
+fn setup_hashlock_escrow<'a>(
+    env: &Env,
+    escrow_id: u64,
+    preimage: &Bytes,
+) -> (VaultixEscrowClient<'a>, token::Client<'a>, Address, Address) {
+    let mut milestone = single_milestone(env, 1_000).get(0).unwrap();
+    milestone.hashlock = OptionalHash::Some(env.crypto().sha256(preimage));
+    let fixture = setup_funded_escrow(env, escrow_id, &vec![env, milestone], 1_700_086_400);
+
+    (
+        fixture.client,
+        fixture.token_client,
+        fixture.depositor,
+        fixture.recipient,
+    )
+}
+
+#[test]
+fn test_claim_with_preimage() {
+    let env = Env::default();
+    let escrow_id = 87u64;
+    let preimage = Bytes::from_slice(&env, b"swap secret 42");
+    let (client, token_client, _, recipient) = setup_hashlock_escrow(&env, escrow_id, &preimage);
+
+    let wrong = Bytes::from_slice(&env, b"guess");
+    assert_eq!(
+        client.try_claim_with_preimage(&escrow_id, &0, &wrong),
+        Err(Ok(Error::InvalidProof))
+    );
+
+    client.claim_with_preimage(&escrow_id, &0, &preimage);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Completed);
+
+    // The revealed preimage is published for the other leg of the swap
+    let events = env.events().all();
+    let claimed = events
+        .iter()
+        .find(|event| {
+            event.1
+                == (
+                    Symbol::new(&env, "Vaultix"),
+                    Symbol::new(&env, "HashlockClaimed"),
+                    escrow_id,
+                    0u32,
+                )
+                    .into_val(&env)
+        })
+        .unwrap();
+    let published: Bytes = claimed.2.into_val(&env);
+    assert_eq!(published, preimage);
+}
+
+#[test]
+fn test_hashlock_expires_and_depositor_reclaims() {
+    let env = Env::default();
+    let escrow_id = 88u64;
+    let preimage = Bytes::from_slice(&env, b"swap secret 42");
+    let (client, token_client, depositor, recipient) =
+        setup_hashlock_escrow(&env, escrow_id, &preimage);
+
+    // Not reclaimable while the hashlock is live
+    assert_eq!(
+        client.try_reclaim_overdue_milestone(&escrow_id, &0),
+        Err(Ok(Error::MilestoneNotOverdue))
+    );
+
+    set_timestamp(&env, 1_700_086_401);
+    assert_eq!(
+        client.try_claim_with_preimage(&escrow_id, &0, &preimage),
+        Err(Ok(Error::InvalidDeadline))
+    );
+    assert_eq!(client.get_overdue_milestones(&escrow_id), vec![&env, 0u32]);
+
+    client.reclaim_overdue_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&depositor), 1_000);
+    assert_eq!(token_client.balance(&recipient), 0);
+}
+
+#[test]
+fn test_depositor_cannot_pull_back_live_hashlock() {
+    let env = Env::default();
+    let escrow_id = 103u64;
+    let preimage = Bytes::from_slice(&env, b"swap secret 42");
+    let (client, token_client, depositor, recipient) =
+        setup_hashlock_escrow(&env, escrow_id, &preimage);
+
+    assert_eq!(
+        client.try_cancel_escrow(&escrow_id),
+        Err(Ok(Error::MilestoneNotOverdue))
+    );
+    assert_eq!(
+        client.try_raise_dispute(&escrow_id, &depositor),
+        Err(Ok(Error::MilestoneNotOverdue))
+    );
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Active);
+
+    // A hashlocked milestone the deposit does not cover yet locks nothing
+    token::StellarAssetClient::new(&env, &token_client.address).mint(&depositor, &2_000);
+    let mut milestones = single_milestone(&env, 1_000);
+    let mut locked = single_milestone(&env, 1_000).get(0).unwrap();
+    locked.hashlock = OptionalHash::Some(env.crypto().sha256(&preimage));
+    milestones.push_back(locked);
+    for partial_id in [104u64, 105u64] {
+        client.create_escrow(
+            &partial_id,
+            &depositor,
+            &recipient,
+            &token_client.address,
+            &milestones,
+            &1_700_086_400u64,
+        );
+        client.deposit_partial(&partial_id, &1_000);
+        assert_eq!(
+            client.try_claim_with_preimage(&partial_id, &1, &preimage),
+            Err(Ok(Error::MilestoneNotFunded))
+        );
+    }
+    client.cancel_escrow(&104u64);
+    assert_eq!(token_client.balance(&depositor), 1_000);
+    client.raise_dispute(&105u64, &depositor);
+    assert_eq!(client.get_state(&105u64), EscrowStatus::Disputed);
+
+    // Once the timelock has passed the depositor may cancel
+    set_timestamp(&env, 1_700_086_401);
+    client.cancel_escrow(&escrow_id);
+    assert_eq!(token_client.balance(&depositor), 2_000);
+    assert_eq!(token_client.balance(&recipient), 0);
+}
+
+#[test]
+fn test_hashlock_validation() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &1_000);
+
+    let mut milestone = single_milestone(&env, 1_000).get(0).unwrap();
+    milestone.hashlock = OptionalHash::Some(BytesN::from_array(&env, &[0u8; 32]));
+    assert_eq!(
+        client.try_create_escrow(
+            &89u64,
+            &depositor,
+            &recipient,
+            &token_address,
+            &vec![&env, milestone],
+            &1706400000u64,
+        ),
+        Err(Ok(Error::InvalidContentHash))
+    );
+
+    // Milestones without a hashlock cannot be claimed with a preimage
+    client.create_and_fund_escrow(
+        &89u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &single_milestone(&env, 1_000),
+        &1706400000u64,
+    );
+    assert_eq!(
+        client.try_claim_with_preimage(&89u64, &0, &Bytes::from_slice(&env, b"secret")),
+        Err(Ok(Error::WrongReleaseMode))
+    );
+}
+
+fn sign_voucher(
+    env: &Env,
+    contract_id: &Address,
+    signing_key: &SigningKey,
+    voucher: &ReleaseVoucher,
+) -> BytesN<64> {
+    let message = (contract_id.clone(), *voucher).to_xdr(env);
+    let mut buf = [0u8; 256];
+    let len = message.len() as usize;
+    message.copy_into_slice(&mut buf[..len]);
+    BytesN::from_array(env, &signing_key.sign(&buf[..len]).to_bytes())
+}
+
+/// Creates a funded two-milestone escrow whose depositor has registered a
+/// voucher key
+fn setup_voucher_escrow<'a>(
+    env: &Env,
+    escrow_id: u64,
+    signing_key: &SigningKey,
+) -> (VaultixEscrowClient<'a>, token::Client<'a>, Address) {
+    let fixture = setup_funded_escrow(env, escrow_id, &two_milestones(env), 1706400000);
+    fixture.client.register_voucher_key(
+        &fixture.depositor,
+        &BytesN::from_array(env, &signing_key.verifying_key().to_bytes()),
+    );
+
+    (fixture.client, fixture.token_client, fixture.recipient)
+}
+
+#[test]
+fn test_redeem_release_voucher() {
+    let env = Env::default();
+    let escrow_id = 90u64;
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+    let (client, token_client, recipient) = setup_voucher_escrow(&env, escrow_id, &signing_key);
+
+    let voucher = ReleaseVoucher {
+        escrow_id,
+        milestone_index: 0,
+        nonce: 1,
+        expiry: 1_700_003_600,
+    };
+    let signature = sign_voucher(&env, &client.address, &signing_key, &voucher);
+    client.redeem_voucher(&voucher, &signature);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+
+    // A used nonce cannot be spent again, even for another milestone
+    let replay = ReleaseVoucher {
+        milestone_index: 1,
+        ..voucher
+    };
+    let replay_signature = sign_voucher(&env, &client.address, &signing_key, &replay);
+    assert_eq!(
+        client.try_redeem_voucher(&replay, &replay_signature),
+        Err(Ok(Error::InvalidProof))
+    );
+
+    // Expired vouchers are refused
+    let late = ReleaseVoucher {
+        milestone_index: 1,
+        nonce: 2,
+        ..voucher
+    };
+    let late_signature = sign_voucher(&env, &client.address, &signing_key, &late);
+    set_timestamp(&env, 1_700_003_601);
+    assert_eq!(
+        client.try_redeem_voucher(&late, &late_signature),
+        Err(Ok(Error::InvalidProof))
+    );
+    assert_eq!(token_client.balance(&recipient), 1_000);
+}
+
+#[test]
+fn test_redeem_voucher_requires_registered_key() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&depositor, &1_000);
+
+    client.create_and_fund_escrow(
+        &92u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &single_milestone(&env, 1_000),
+        &1706400000u64,
+    );
+
+    let voucher = ReleaseVoucher {
+        escrow_id: 92,
+        milestone_index: 0,
+        nonce: 1,
+        expiry: u64::MAX,
+    };
+    let signature = BytesN::from_array(&env, &[1u8; 64]);
+    assert_eq!(
+        client.try_redeem_voucher(&voucher, &signature),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+}
+
+fn template_milestone(env: &Env, title: &str, share_bps: u32) -> TemplateMilestone {
+    TemplateMilestone {
+        title: String::from_str(env, title),
+        share_bps,
+    }
+}
+
+#[test]
+fn test_create_escrow_from_template() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_timestamp(&env, 1_700_000_000);
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let merchant = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    let layout = vec![
+        &env,
+        template_milestone(&env, "Design", 3_000),
+        template_milestone(&env, "Build", 3_000),
+        template_milestone(&env, "Launch", 4_000),
+    ];
+    let template_id = client.register_template(
+        &merchant,
+        &token_address,
+        &layout,
+        &604_800,
+        &FeePayer::Recipient,
+    );
+    assert_eq!(template_id, 1);
+
+    let template = client.get_template(&template_id);
+    assert_eq!(template.owner, merchant);
+    assert_eq!(template.milestones, layout);
+
+    // 1_001 splits into 300 / 300 / 401: the dust lands on the last milestone
+    client.create_escrow_from_template(&93u64, &template_id, &1_001, &recipient);
+    let escrow = client.get_escrow(&93u64);
+    assert_eq!(escrow.depositor, OptionalAddress::Some(merchant.clone()));
+    assert_eq!(escrow.recipient, recipient);
+    assert_eq!(escrow.total_amount, 1_001);
+    assert_eq!(escrow.deadline, 1_700_604_800);
+    assert_eq!(escrow.milestones.get(0).unwrap().amount, 300);
+    assert_eq!(escrow.milestones.get(1).unwrap().amount, 300);
+    assert_eq!(escrow.milestones.get(2).unwrap().amount, 401);
+    assert_eq!(
+        escrow.milestones.get(2).unwrap().title,
+        String::from_str(&env, "Launch")
+    );
+
+    // Templates are reusable and get sequential ids
+    client.create_escrow_from_template(&94u64, &template_id, &2_000, &recipient);
+    assert_eq!(client.get_escrow(&94u64).total_amount, 2_000);
+    let second_id = client.register_template(
+        &merchant,
+        &token_address,
+        &vec![&env, template_milestone(&env, "All", 10_000)],
+        &3_600,
+        &FeePayer::Recipient,
+    );
+    assert_eq!(second_id, 2);
+}
+
+#[test]
+fn test_template_with_depositor_paying_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(100)); // 1%
+
+    let merchant = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&merchant, &20_000);
+
+    let template_id = client.register_template(
+        &merchant,
+        &token_address,
+        &vec![
+            &env,
+            template_milestone(&env, "First half", 5_000),
+            template_milestone(&env, "Second half", 5_000),
+        ],
+        &604_800,
+        &FeePayer::Depositor,
+    );
+    client.create_escrow_from_template(&95u64, &template_id, &10_000, &recipient);
+
+    // Each 5_000 share is grossed up so the payout after 1% covers it
+    let escrow = client.get_escrow(&95u64);
+    assert_eq!(escrow.milestones.get(0).unwrap().amount, 5_051);
+    assert_eq!(escrow.total_amount, 10_102);
+
+    client.deposit_funds_direct(&95u64);
+    client.release_milestone(&95u64, &0);
+    client.release_milestone(&95u64, &1);
+    assert_eq!(token_client.balance(&recipient), 10_002);
+}
+
+#[test]
+fn test_register_template_validation() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let merchant = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+    let (_, unlisted_token) = create_test_token(&env, &admin);
+    allow_token(&env, &client, &token_address);
+
+    let register = |token: &Address, layout: Vec<TemplateMilestone>, offset: u64| {
+        client.try_register_template(&merchant, token, &layout, &offset, &FeePayer::Recipient)
+    };
+
+    assert_eq!(
+        register(&token_address, Vec::new(&env), 3_600),
+        Err(Ok(Error::NoMilestones))
+    );
+    assert_eq!(
+        register(
+            &token_address,
+            vec![
+                &env,
+                template_milestone(&env, "A", 5_000),
+                template_milestone(&env, "B", 4_000)
+            ],
+            3_600
+        ),
+        Err(Ok(Error::InvalidSplits))
+    );
+    assert_eq!(
+        register(
+            &token_address,
+            vec![&env, template_milestone(&env, "", 10_000)],
+            3_600
+        ),
+        Err(Ok(Error::EmptyMilestoneTitle))
+    );
+    assert_eq!(
+        register(
+            &token_address,
+            vec![&env, template_milestone(&env, "A", 10_000)],
+            0
+        ),
+        Err(Ok(Error::InvalidDeadline))
+    );
+    assert_eq!(
+        register(
+            &unlisted_token,
+            vec![&env, template_milestone(&env, "A", 10_000)],
+            3_600
+        ),
+        Err(Ok(Error::TokenNotAllowed))
+    );
+
+    assert_eq!(
+        client.try_create_escrow_from_template(&96u64, &7u64, &1_000, &recipient),
+        Err(Ok(Error::EscrowNotFound))
+    );
+}
+
+#[test]
+fn test_invoice_paid_by_anyone_becomes_active_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_timestamp(&env, 1_700_000_000);
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let payer = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&payer, &1_000);
+
+    let escrow_id = 97u64;
+    client.create_invoice(
+        &escrow_id,
+        &recipient,
+        &single_milestone(&env, 1_000),
+        &token_address,
+        &1706400000u64,
+        &None,
+    );
+
+    // The unpaid invoice is a `Created` escrow without a depositor
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Created);
+    assert_eq!(escrow.depositor, OptionalAddress::None);
+    assert_eq!(escrow.payer, OptionalAddress::None);
+    assert_eq!(escrow.recipient, recipient);
+    assert_eq!(escrow.total_amount, 1_000);
+
+    // Depositor actions have nobody to act for yet
+    assert_eq!(
+        client.try_deposit_funds_direct(&escrow_id),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+    assert_eq!(
+        client.try_cancel_escrow(&escrow_id),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+    assert_eq!(
+        client.try_raise_dispute(&escrow_id, &recipient),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+
+    // The invoice reserves its id
+    assert_eq!(
+        client.try_create_escrow(
+            &escrow_id,
+            &payer,
+            &recipient,
+            &token_address,
+            &single_milestone(&env, 1_000),
+            &1706400000u64,
+        ),
+        Err(Ok(Error::EscrowAlreadyExists))
+    );
+
+    // Regular escrows cannot be paid as invoices
+    client.create_escrow(
+        &99u64,
+        &payer,
+        &recipient,
+        &token_address,
+        &single_milestone(&env, 1_000),
+        &1706400000u64,
+    );
+    assert_eq!(
+        client.try_pay_invoice(&99u64, &payer),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+
+    client.pay_invoice(&escrow_id, &payer);
+    assert_eq!(token_client.balance(&payer), 0);
+    assert_eq!(token_client.balance(&contract_id), 1_000);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.depositor, OptionalAddress::Some(payer.clone()));
+    assert_eq!(escrow.status, EscrowStatus::Active);
+    assert_eq!(escrow.funded_amount, 1_000);
+
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+
+    assert_eq!(
+        client.try_pay_invoice(&escrow_id, &payer),
+        Err(Ok(Error::EscrowAlreadyFunded))
+    );
+    assert_eq!(
+        client.try_pay_invoice(&100u64, &payer),
+        Err(Ok(Error::EscrowNotFound))
+    );
+}
+
+#[test]
+fn test_invoice_designated_payer_and_cancel() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_timestamp(&env, 1_700_000_000);
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let payer = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    allow_token(&env, &client, &token_address);
+    token_admin.mint(&payer, &1_000);
+    token_admin.mint(&stranger, &1_000);
+
+    assert_eq!(
+        client.try_create_invoice(
+            &98u64,
+            &recipient,
+            &single_milestone(&env, 1_000),
+            &token_address,
+            &1706400000u64,
+            &Some(recipient.clone()),
+        ),
+        Err(Ok(Error::SelfDealing))
+    );
+    assert_eq!(
+        client.try_create_invoice(
+            &98u64,
+            &recipient,
+            &single_milestone(&env, 1_000),
+            &token_address,
+            &1_700_000_000u64,
+            &None,
+        ),
+        Err(Ok(Error::InvalidDeadline))
+    );
+
+    client.create_invoice(
+        &98u64,
+        &recipient,
+        &single_milestone(&env, 1_000),
+        &token_address,
+        &1706400000u64,
+        &Some(payer.clone()),
+    );
+    assert_eq!(
+        client.try_pay_invoice(&98u64, &stranger),
+        Err(Ok(Error::UnauthorizedAccess))
+    );
+    assert_eq!(token_client.balance(&stranger), 1_000);
+
+    assert_eq!(
+        client.get_escrow(&98u64).payer,
+        OptionalAddress::Some(payer.clone())
+    );
+
+    // Withdrawing the invoice waits for the contract to be unpaused
+    client.set_paused(&true);
+    assert_eq!(
+        client.try_cancel_invoice(&98u64),
+        Err(Ok(Error::ContractPaused))
+    );
+    client.set_paused(&false);
+
+    client.cancel_invoice(&98u64);
+    assert_eq!(client.get_state(&98u64), EscrowStatus::Cancelled);
+    assert_eq!(
+        client.try_pay_invoice(&98u64, &payer),
+        Err(Ok(Error::EscrowAlreadyFunded))
+    );
+    assert_eq!(token_client.balance(&payer), 1_000);
+}
@@ -22,7 +22,12 @@ fn test_admin_upgrade_and_state_preservation() {
     milestones.push_back(Milestone {
         amount: 1000,
         status: MilestoneStatus::Pending,
-        description: Symbol::new(&env, "Test"),
+        title: String::from_str(&env, "Test"),
+        content_hash: OptionalHash::None,
+        token: OptionalAddress::None,
+        due_date: None,
+        recipient: OptionalAddress::None,
+        hashlock: OptionalHash::None,
     });
     let deadline = 1706400000u64;
     client.create_escrow(&1u64, &depositor, &recipient, &token_address, &milestones, &deadline);
@@ -34,7 +39,7 @@ fn test_admin_upgrade_and_state_preservation() {
 
     // State should be preserved
     let escrow = client.get_escrow(&1u64);
-    assert_eq!(escrow.depositor, depositor);
+    assert_eq!(escrow.depositor, OptionalAddress::Some(depositor.clone()));
     assert_eq!(escrow.recipient, recipient);
     assert_eq!(escrow.total_amount, 1000);
 